@@ -1,5 +1,6 @@
 use std::{
     fmt::{self, Display},
+    path::PathBuf,
     string::ToString,
 };
 
@@ -35,6 +36,51 @@ pub enum Action {
     Error(String),
     Back,
     Help,
+    Filter,
+    Sort(usize),
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Confirm(String, Box<Action>),
+    DeleteRow(i32),
+    EditExternally,
+    OpenEditor(PathBuf),
+    EditorClosed(PathBuf),
+    EditorFailed(String),
+    TogglePreview,
+    Discard(Box<Action>),
+    Undo,
+    Redo,
+    History,
+    ShowHistory(i32),
+    Restore,
+    RestoreRevision(i32),
+    Publish,
+    Unpublish,
+    DraftsOnly,
+    ToggleDetails,
+    Mark,
+    MarkUp,
+    MarkDown,
+    MarkAll,
+    BulkTag,
+    BulkAuthor,
+    BulkStatus,
+    Choose(String, Vec<(String, Action)>),
+    BulkEdit(Bulk),
+    Info(String),
+}
+
+/// A change made at once to every marked row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Bulk {
+    Delete,
+    /// Add the tag with this id.
+    Tag(i32),
+    /// Give the posts to the user with this id.
+    Author(i32),
+    Status(String),
 }
 
 impl Action {
@@ -43,6 +89,9 @@ impl Action {
             Action::TabChange(_) => true,
             Action::CrudEdit(_, _) => true,
             Action::CrudNew(_) => true,
+            Action::Confirm(_, _) => true,
+            Action::ShowHistory(_) => true,
+            Action::Choose(_, _) => true,
             _ => false,
         }
     }
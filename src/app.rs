@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use color_eyre::eyre::Result;
 use crossterm::event::KeyEvent;
@@ -9,17 +12,20 @@ use ratatui::{
 };
 use sea_orm::{Database, DatabaseConnection};
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::{process::Command, sync::mpsc};
 
 use crate::{
     action::Action,
     area::Area,
     components::{
-        crudedit::CrudEdit, crudlist::CrudList, footer::Footer, tabbar::TabBar, Component,
+        chooser::Chooser, confirm::Confirm, crudedit::CrudEdit, crudlist::CrudList, footer::Footer,
+        history::History, tabbar::TabBar, Component,
     },
     config::Config,
     data::{
         posts::{PostEdit, Posts},
+        schedule::{Schedule, ScheduleEdit},
+        tags::{TagEdit, Tags},
         users::{UserEdit, Users},
     },
     mode::Mode,
@@ -34,6 +40,8 @@ pub struct App {
     pub components: Vec<Box<dyn Component + Send>>,
     pub should_quit: bool,
     pub should_suspend: bool,
+    /// File to open in the external editor, in place of suspending to the shell.
+    pub editor: Option<PathBuf>,
     pub mode: Mode,
     pub last_tick_key_events: Vec<KeyEvent>,
 }
@@ -50,11 +58,19 @@ impl App {
                 Box::new(Footer::new()),
                 Box::new(CrudList::new(Posts::default(), Mode::Posts)),
                 Box::new(CrudEdit::new(PostEdit::default(), Mode::Posts)),
+                Box::new(CrudList::new(Schedule::default(), Mode::Schedule)),
+                Box::new(CrudEdit::new(ScheduleEdit::default(), Mode::Schedule)),
+                Box::new(CrudList::new(Tags::default(), Mode::Tags)),
+                Box::new(CrudEdit::new(TagEdit::default(), Mode::Tags)),
                 Box::new(CrudList::new(Users::default(), Mode::Users)),
                 Box::new(CrudEdit::new(UserEdit::default(), Mode::Users)),
+                Box::new(History::new()),
+                Box::new(Chooser::new()),
+                Box::new(Confirm::new()),
             ],
             should_quit: false,
             should_suspend: false,
+            editor: None,
             config,
             db: None,
             mode,
@@ -73,29 +89,49 @@ impl App {
             .split(f.size());
 
         for c in self.components.iter_mut() {
-            let layout_idx = match c.component_type() {
-                Area::Header => 0,
-                Area::Footer => 2,
-                Area::Main => 1,
+            let area = match c.component_type() {
+                Area::Header => layout[0],
+                Area::Footer => layout[2],
+                Area::Main => layout[1],
+                Area::Popup => f.size(),
             };
             if c.focused() {
-                c.draw(f, layout[layout_idx])?;
+                c.draw(f, area)?;
             }
         }
         Ok(())
     }
 
+    fn has_unsaved_changes(&self) -> bool {
+        self.components.iter().any(|c| c.has_unsaved_changes())
+    }
+
+    /// Whether a popup is open, in which case it gets the key events instead of the keymap
+    /// and the other components.
+    fn popup_open(&self) -> bool {
+        self.components
+            .iter()
+            .any(|c| c.component_type() == Area::Popup && c.focused())
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let (action_tx, mut action_rx) = mpsc::unbounded_channel();
         action_tx.send(Action::TabChange(Mode::default()))?;
 
         let mut tui = tui::Tui::new()?
             .tick_rate(self.tick_rate)
-            .frame_rate(self.frame_rate);
+            .frame_rate(self.frame_rate)
+            .paste(true);
         // tui.mouse(true);
         tui.enter()?;
 
-        self.db = Some(Database::connect(self.config.db.clone()).await?);
+        match Database::connect(self.config.db.clone()).await {
+            Ok(cnx) => self.db = Some(cnx),
+            Err(e) => action_tx.send(Action::Error(format!(
+                "Cannot connect to the database: {}",
+                e
+            )))?,
+        }
         for component in self.components.iter_mut() {
             component.register_db_handler(self.db.clone())?;
         }
@@ -114,11 +150,13 @@ impl App {
 
         loop {
             if let Some(e) = tui.next().await {
+                let popup_open = self.popup_open();
                 match e {
                     tui::Event::Quit => action_tx.send(Action::Quit)?,
                     tui::Event::Tick => action_tx.send(Action::Tick)?,
                     tui::Event::Render => action_tx.send(Action::Render)?,
                     tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
+                    tui::Event::Key(_) if popup_open => {}
                     tui::Event::Key(key) => {
                         if let Some(keymap) = self.config.keybindings.get(&self.mode) {
                             if let Some(action) = keymap.get(&vec![key]) {
@@ -140,8 +178,13 @@ impl App {
                     _ => {}
                 }
                 for component in self.components.iter_mut() {
-                    if let Some(action) = component.handle_events(Some(e.clone()))? {
-                        action_tx.send(action)?;
+                    if popup_open && component.component_type() != Area::Popup {
+                        continue;
+                    }
+                    match component.handle_events(Some(e.clone())) {
+                        Ok(Some(action)) => action_tx.send(action)?,
+                        Ok(None) => {}
+                        Err(e) => action_tx.send(Action::Error(format!("{:#}", e)))?,
                     }
                 }
             }
@@ -154,6 +197,15 @@ impl App {
                     Action::Tick => {
                         self.last_tick_key_events.drain(..);
                     }
+                    Action::Quit | Action::NextTab if self.has_unsaved_changes() => {
+                        action_tx.send(Action::Confirm(
+                            "Discard unsaved changes?".to_string(),
+                            Box::new(Action::Discard(Box::new(action.clone()))),
+                        ))?;
+                        continue;
+                    }
+                    // The components drop their changes, after which the action goes ahead.
+                    Action::Discard(ref action) => action_tx.send((**action).clone())?,
                     Action::Quit => self.should_quit = true,
                     Action::Suspend => self.should_suspend = true,
                     Action::Resume => self.should_suspend = false,
@@ -182,24 +234,46 @@ impl App {
                             }
                         })?;
                     }
+                    Action::OpenEditor(ref path) => {
+                        self.editor = Some(path.clone());
+                        self.should_suspend = true;
+                    }
                     Action::TabChange(mode) => self.mode = mode,
                     Action::NextTab => action_tx.send(Action::TabChange(self.mode.next()))?,
                     _ => {}
                 }
                 for component in self.components.iter_mut() {
                     if component.focused() || action.is_focus_changed() {
-                        if let Some(action) = component.update(action.clone()).await? {
-                            action_tx.send(action)?
-                        };
+                        // A failed action is reported and leaves the component as it was, so
+                        // that, say, a form that cannot be saved keeps its content.
+                        match component.update(action.clone()).await {
+                            Ok(Some(action)) => action_tx.send(action)?,
+                            Ok(None) => {}
+                            Err(e) => action_tx.send(Action::Error(format!("{:#}", e)))?,
+                        }
                     }
                 }
             }
             if self.should_suspend {
-                tui.suspend()?;
+                if let Some(path) = self.editor.take() {
+                    // The editor has the terminal until it exits, rather than the shell.
+                    tui.exit()?;
+                    let action = match editor_command(&path).status().await {
+                        Ok(status) if status.success() => Action::EditorClosed(path),
+                        Ok(status) => {
+                            Action::EditorFailed(format!("The editor failed, {}", status))
+                        }
+                        Err(e) => Action::EditorFailed(format!("Cannot start the editor: {}", e)),
+                    };
+                    action_tx.send(action)?;
+                } else {
+                    tui.suspend()?;
+                }
                 action_tx.send(Action::Resume)?;
                 tui = tui::Tui::new()?
                     .tick_rate(self.tick_rate)
-                    .frame_rate(self.frame_rate);
+                    .frame_rate(self.frame_rate)
+                    .paste(true);
                 // tui.mouse(true);
                 tui.enter()?;
             } else if self.should_quit {
@@ -215,3 +289,15 @@ impl App {
         Ok(())
     }
 }
+
+/// The command editing `path` in `$VISUAL`, or `$EDITOR`, falling back to `vi`. The variable
+/// may hold arguments as well, as in `code --wait`.
+fn editor_command(path: &Path) -> Command {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_default();
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("vi"));
+    command.args(words).arg(path);
+    command
+}
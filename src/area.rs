@@ -3,4 +3,5 @@ pub enum Area {
   Header,
   Footer,
   Main,
+  Popup,
 }
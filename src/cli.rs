@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
 use crate::utils::version;
 
@@ -18,4 +18,78 @@ pub struct Cli {
     default_value_t = 4.0
   )]
   pub frame_rate: f64,
+
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+/// Run a single operation without starting the TUI.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+  /// Manage posts
+  Posts {
+    #[command(subcommand)]
+    action: CrudCommand,
+  },
+  /// Manage tags
+  Tags {
+    #[command(subcommand)]
+    action: CrudCommand,
+  },
+  /// Manage users
+  Users {
+    #[command(subcommand)]
+    action: CrudCommand,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CrudCommand {
+  /// List every record
+  List {
+    #[arg(short = 'o', long, value_enum, default_value_t = Format::Table, help = "Output format")]
+    format: Format,
+  },
+  /// Show a single record
+  Get {
+    id: i32,
+    #[arg(short = 'o', long, value_enum, default_value_t = Format::Table, help = "Output format")]
+    format: Format,
+  },
+  /// Create a record and print its id
+  Create {
+    #[arg(short, long = "set", value_name = "FIELD=VALUE", value_parser = parse_field, help = "Set a field, can be repeated")]
+    fields: Vec<(String, String)>,
+  },
+  /// Update the given fields of a record
+  Update {
+    id: i32,
+    #[arg(short, long = "set", value_name = "FIELD=VALUE", value_parser = parse_field, help = "Set a field, can be repeated")]
+    fields: Vec<(String, String)>,
+  },
+  /// Delete a record
+  Delete { id: i32 },
+}
+
+/// Output format of the commands printing records.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+  /// Aligned columns, as shown in the TUI
+  #[default]
+  Table,
+  /// A JSON array of the full records
+  Json,
+  /// One JSON record per line
+  Jsonl,
+  /// Comma separated values of the full records
+  Csv,
+  /// A YAML sequence of the full records
+  Yaml,
+}
+
+fn parse_field(raw: &str) -> Result<(String, String), String> {
+  raw
+    .split_once('=')
+    .map(|(field, value)| (field.trim().to_string(), value.to_string()))
+    .ok_or_else(|| format!("expected FIELD=VALUE, got `{raw}`"))
 }
@@ -0,0 +1,287 @@
+use std::fmt::{self, Display};
+
+use color_eyre::eyre::{Report, Result};
+use sea_orm::{Database, DatabaseConnection};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    cli::{Command, CrudCommand, Format},
+    config::Config,
+    data::{
+        posts::{PostEdit, Posts},
+        tags::{TagEdit, Tags},
+        users::{UserEdit, Users},
+        CrudData, CrudRow,
+    },
+};
+
+// Exit codes, as defined by sysexits(3).
+pub const EX_USAGE: i32 = 64;
+pub const EX_DATAERR: i32 = 65;
+pub const EX_NOINPUT: i32 = 66;
+pub const EX_UNAVAILABLE: i32 = 69;
+pub const EX_CONFIG: i32 = 78;
+
+/// An error that carries the exit code the process should terminate with.
+#[derive(Debug)]
+pub struct CommandError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl CommandError {
+    fn report(code: i32, message: impl Display) -> Report {
+        Report::new(CommandError {
+            code,
+            message: message.to_string(),
+        })
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// The exit code matching `err`, `EXIT_FAILURE` unless it is a [`CommandError`].
+pub fn exit_code(err: &Report) -> i32 {
+    err.downcast_ref::<CommandError>()
+        .map_or(libc::EXIT_FAILURE, |x| x.code)
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::new().map_err(|e| CommandError::report(EX_CONFIG, e))?;
+        if config.db.is_empty() {
+            return Err(CommandError::report(
+                EX_CONFIG,
+                "No database configured, set `db` in the configuration file",
+            ));
+        }
+        let cnx = Database::connect(config.db.clone())
+            .await
+            .map_err(|e| CommandError::report(EX_UNAVAILABLE, e))?;
+        let result = match self {
+            Command::Posts { action } => run::<Posts, PostEdit>(action, &cnx, &config).await,
+            Command::Tags { action } => run::<Tags, TagEdit>(action, &cnx, &config).await,
+            Command::Users { action } => run::<Users, UserEdit>(action, &cnx, &config).await,
+        };
+        cnx.close().await?;
+        result
+    }
+}
+
+async fn run<D: CrudData, R: CrudRow>(
+    action: CrudCommand,
+    cnx: &DatabaseConnection,
+    config: &Config,
+) -> Result<()> {
+    match action {
+        CrudCommand::List { format } => {
+            let data = load::<D>(cnx, config).await?;
+            let indices: Vec<usize> = (0..data.num_rows()).collect();
+            print_records(&data, &indices, format, false)?;
+        }
+        CrudCommand::Get { id, format } => {
+            let data = find::<D>(cnx, config, id).await?;
+            print_records(&data, &[0], format, true)?;
+        }
+        CrudCommand::Create { fields } => {
+            let mut row = row::<R>(cnx, config);
+            row.new().await?;
+            save(&mut row, fields).await?;
+            if let Some(id) = row.id() {
+                println!("{}", id);
+            }
+        }
+        CrudCommand::Update { id, fields } => {
+            let mut row = row::<R>(cnx, config);
+            row.edit(id).await?;
+            if row.id() != Some(id) {
+                return Err(not_found(id));
+            }
+            save(&mut row, fields).await?;
+        }
+        CrudCommand::Delete { id } => {
+            let data = find::<D>(cnx, config, id).await?;
+            data.delete(0).await?;
+        }
+    }
+    Ok(())
+}
+
+fn data<D: CrudData>(cnx: &DatabaseConnection, config: &Config) -> D {
+    let mut data = D::default();
+    data.set_db(Some(cnx.clone()));
+    data.set_config(config.clone());
+    data
+}
+
+async fn load<D: CrudData>(cnx: &DatabaseConnection, config: &Config) -> Result<D> {
+    let mut data = data::<D>(cnx, config);
+    data.refresh().await?;
+    while data.load_more().await? {}
+    Ok(data)
+}
+
+fn row<R: CrudRow>(cnx: &DatabaseConnection, config: &Config) -> R {
+    let mut row = R::default();
+    row.set_db(Some(cnx.clone()));
+    row.set_config(config.clone());
+    row
+}
+
+async fn save<R: CrudRow>(row: &mut R, fields: Vec<(String, String)>) -> Result<()> {
+    for (name, value) in fields {
+        row.set_field(&name, &value)
+            .map_err(|e| CommandError::report(EX_USAGE, e))?;
+    }
+    row.validate()
+        .map_err(|e| CommandError::report(EX_DATAERR, e))?;
+    row.save().await
+}
+
+/// The row with database id `id`, loaded on its own.
+async fn find<D: CrudData>(cnx: &DatabaseConnection, config: &Config, id: i32) -> Result<D> {
+    let mut data = data::<D>(cnx, config);
+    if !data.load_id(id).await? {
+        return Err(not_found(id));
+    }
+    Ok(data)
+}
+
+fn not_found(id: i32) -> Report {
+    CommandError::report(EX_NOINPUT, format!("No record with id {}", id))
+}
+
+/// Print the records at `indices` in `format`. A `single` record is printed on its own rather
+/// than as a one element list.
+fn print_records<D: CrudData>(
+    data: &D,
+    indices: &[usize],
+    format: Format,
+    single: bool,
+) -> Result<()> {
+    if format == Format::Table {
+        let mut headers = vec!["Id".to_string()];
+        headers.extend(data.headers());
+        let all_rows = data.rows();
+        let rows: Vec<Vec<String>> = indices
+            .iter()
+            .map(|i| {
+                let mut cells = vec![data.to_db_id(*i).to_string()];
+                cells.extend(all_rows[*i].iter().cloned());
+                cells
+            })
+            .collect();
+        if single {
+            for (header, value) in headers.iter().zip(&rows[0]) {
+                println!("{}: {}", header, value);
+            }
+        } else {
+            print_table(&headers, &rows);
+        }
+        return Ok(());
+    }
+
+    let records = indices
+        .iter()
+        .map(|i| data.record(*i))
+        .collect::<Result<Vec<JsonValue>>>()?;
+    match format {
+        Format::Json if single => println!("{}", serde_json::to_string_pretty(&records[0])?),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records)?),
+        Format::Jsonl => {
+            for record in records.iter() {
+                println!("{}", serde_json::to_string(record)?);
+            }
+        }
+        Format::Yaml if single => print!("{}", serde_yaml::to_string(&records[0])?),
+        Format::Yaml => print!("{}", serde_yaml::to_string(&records)?),
+        Format::Csv => print_csv(&records),
+        Format::Table => unreachable!(),
+    }
+    Ok(())
+}
+
+fn print_csv(records: &[JsonValue]) {
+    let Some(JsonValue::Object(first)) = records.first() else {
+        return;
+    };
+    let columns: Vec<String> = first.keys().cloned().collect();
+    println!(
+        "{}",
+        columns
+            .iter()
+            .map(|x| csv_field(x))
+            .collect::<Vec<String>>()
+            .join(",")
+    );
+    for record in records.iter() {
+        let line = columns
+            .iter()
+            .map(|column| match record.get(column) {
+                None | Some(JsonValue::Null) => String::new(),
+                Some(JsonValue::String(s)) => csv_field(s),
+                Some(value) => csv_field(&value.to_string()),
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+        println!("{}", line);
+    }
+}
+
+/// Quote `value` as per RFC 4180 when it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_table(headers: &[String], rows: &[Vec<String>]) {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|x| x.replace(['\r', '\n'], " ")).collect())
+        .collect();
+    let mut widths: Vec<usize> = headers.iter().map(|x| x.chars().count()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", format_row(headers));
+    for row in rows.iter() {
+        println!("{}", format_row(row));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}
@@ -12,9 +12,12 @@ use crate::{
     tui::{Event, Frame},
 };
 
+pub mod chooser;
+pub mod confirm;
 pub mod crudedit;
 pub mod crudlist;
 pub mod footer;
+pub mod history;
 pub mod tabbar;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
@@ -137,5 +140,10 @@ pub trait Component {
         false
     }
 
+    /// Whether the component holds edits that quitting or leaving it would lose.
+    fn has_unsaved_changes(&self) -> bool {
+        false
+    }
+
     fn component_type(&self) -> Area;
 }
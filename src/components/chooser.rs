@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Borders, Clear},
+};
+
+use super::Component;
+use crate::{
+    action::Action,
+    area::Area,
+    tui::Frame,
+    widgets::picker::{Picker, PickerItem},
+};
+
+/// A modal list of choices, each sending its own action.
+///
+/// It opens on [`Action::Choose`] and, while open, gets every key: typing narrows the list
+/// down, `Enter` sends the action of the highlighted choice and `Esc` dismisses it.
+#[derive(Default)]
+pub struct Chooser {
+    title: String,
+    picker: Picker,
+    actions: Vec<Action>,
+}
+
+impl Chooser {
+    pub fn new() -> Self {
+        Chooser::default()
+    }
+}
+
+#[async_trait]
+impl Component for Chooser {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if self.actions.is_empty() {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Enter => {
+                self.picker.toggle();
+                let chosen = self.picker.selected().first().and_then(|x| x.id);
+                if let Some(i) = chosen {
+                    let mut actions = std::mem::take(&mut self.actions);
+                    return Ok(Some(actions.swap_remove(i as usize)));
+                }
+                return Ok(None);
+            }
+            KeyCode::Esc => self.actions.clear(),
+            KeyCode::Up => self.picker.up(),
+            KeyCode::Down => self.picker.down(),
+            KeyCode::Backspace => self.picker.delete_last_char(),
+            KeyCode::Char(c) => self.picker.input(c),
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    async fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::Choose(title, choices) = action {
+            let items = choices
+                .iter()
+                .enumerate()
+                .map(|(i, (label, _))| PickerItem::new(i as i32, label.clone()))
+                .collect();
+            self.title = title;
+            self.picker = Picker::new(items, false);
+            self.actions = choices.into_iter().map(|(_, action)| action).collect();
+            return Ok(Some(Action::Render));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        // Room for the borders, the filter and every choice.
+        let height = area
+            .height
+            .min((self.actions.len() as u16).saturating_add(3).max(5));
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        f.render_widget(Clear, popup);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.title.clone());
+        self.picker.draw(f, popup, block, true);
+        Ok(())
+    }
+
+    fn focused(&self) -> bool {
+        !self.actions.is_empty()
+    }
+
+    fn component_type(&self) -> Area {
+        Area::Popup
+    }
+}
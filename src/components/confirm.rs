@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::Component;
+use crate::{action::Action, area::Area, style::FormStyle, tui::Frame};
+
+/// A modal asking for confirmation before a destructive action is sent.
+///
+/// It opens on [`Action::Confirm`] and, while open, gets every key: `y` sends the wrapped
+/// action, `n` or `Esc` dismisses it and any other key is ignored.
+#[derive(Default)]
+pub struct Confirm {
+    message: String,
+    action: Option<Action>,
+}
+
+impl Confirm {
+    pub fn new() -> Self {
+        Confirm::default()
+    }
+}
+
+#[async_trait]
+impl Component for Confirm {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if self.action.is_none() {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Ok(self.action.take()),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.action = None;
+                Ok(Some(Action::Render))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if let Action::Confirm(message, action) = action {
+            self.message = message;
+            self.action = Some(*action);
+            return Ok(Some(Action::Render));
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = area.height.min(7);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let text = vec![
+            Line::from(self.message.clone()),
+            Line::from(""),
+            Line::from("y : confirm, n : cancel"),
+        ];
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().borders(Borders::ALL).title("Confirm"))
+                .style(FormStyle::normal()),
+            popup,
+        );
+        Ok(())
+    }
+
+    fn focused(&self) -> bool {
+        self.action.is_some()
+    }
+
+    fn component_type(&self) -> Area {
+        Area::Popup
+    }
+}
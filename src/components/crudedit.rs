@@ -1,11 +1,20 @@
+use std::{fs, io::Write, path::PathBuf};
+
 use crate::tui::Event;
 use async_trait::async_trait;
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
-use ratatui::{layout::Rect, Frame};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Paragraph,
+    Frame,
+};
 use sea_orm::DatabaseConnection;
+use tempfile::TempPath;
 
-use crate::{action::Action, area::Area, data::CrudRow, mode::Mode};
+use crate::{
+    action::Action, area::Area, config::Config, data::CrudRow, mode::Mode, style::TableStyle,
+};
 
 use super::Component;
 
@@ -15,6 +24,12 @@ pub struct CrudEdit<T: CrudRow + Send> {
     focused: bool,
     db: Option<DatabaseConnection>,
     data: T,
+    /// Whether saving failed validation, after which the form is checked on every change.
+    validating: bool,
+    /// Field values as loaded or last saved.
+    saved: Vec<String>,
+    /// File of the field open in the external editor, removed when dropped.
+    editing: Option<TempPath>,
 }
 
 impl<T: CrudRow + Send> CrudEdit<T> {
@@ -25,6 +40,56 @@ impl<T: CrudRow + Send> CrudEdit<T> {
             ..Default::default()
         }
     }
+
+    fn edited(&mut self) -> Result<Option<Action>> {
+        self.data.edited();
+        if self.validating {
+            // The errors are shown in the form, the summary is only for the footer.
+            let _ = self.data.validate();
+        }
+        Ok(Some(Action::Render))
+    }
+
+    /// Write the focused field to a Markdown file for an external editor. The file gets a
+    /// random name and is readable by the user only.
+    fn write_focused_field(&mut self) -> Result<PathBuf> {
+        let input = self
+            .data
+            .focused_input()
+            .ok_or_else(|| eyre!("This field cannot be opened in an editor"))?;
+        if input.is_secret() {
+            return Err(eyre!("Secret fields cannot be opened in an editor"));
+        }
+        let mut file = tempfile::Builder::new()
+            .prefix(concat!(env!("CARGO_PKG_NAME"), "-"))
+            .suffix(".md")
+            .tempfile()?;
+        file.write_all(input.value().as_bytes())?;
+        let file = file.into_temp_path();
+        let path = file.to_path_buf();
+        self.editing = Some(file);
+        Ok(path)
+    }
+
+    /// Replace the focused field with the content of the file written by the editor, and
+    /// remove the file.
+    fn read_focused_field(&mut self) -> Result<()> {
+        let file = self
+            .editing
+            .take()
+            .ok_or_else(|| eyre!("No field is open in an editor"))?;
+        let value = fs::read_to_string(&file)?;
+        if let Some(input) = self.data.focused_input() {
+            // Editors end files with a line break, which is not part of the field.
+            let value = value.strip_suffix('\n').unwrap_or(&value);
+            if input.is_multiline() {
+                input.set_value(value);
+            } else {
+                input.set_value(&value.replace('\n', " "));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -35,20 +100,53 @@ impl<T: CrudRow + Send> Component for CrudEdit<T> {
         Ok(())
     }
 
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.data.set_config(config);
+        Ok(())
+    }
+
+    fn handle_events(&mut self, event: Option<Event>) -> Result<Option<Action>> {
+        match event {
+            Some(Event::Paste(text)) if self.focused => {
+                self.data.paste(&text);
+                self.edited()
+            }
+            Some(Event::Key(key)) => self.handle_key_events(key),
+            _ => Ok(None),
+        }
+    }
+
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.focused {
+            return Ok(None);
+        }
+        if let Some(input) = self.data.focused_input() {
+            if input.handle_key(key) {
+                return self.edited();
+            }
+            return Ok(None);
+        }
         if !key.modifiers.contains(KeyModifiers::CONTROL) {
             match key.code {
                 KeyCode::Char(c) => {
                     self.data.input(c);
-                    return Ok(Some(Action::Render));
+                    return self.edited();
                 }
                 KeyCode::Backspace => {
                     self.data.delete_last_char();
-                    return Ok(Some(Action::Render));
+                    return self.edited();
                 }
                 KeyCode::Enter => {
                     self.data.input('\n');
-                    return Ok(Some(Action::Render));
+                    return self.edited();
+                }
+                KeyCode::Left => {
+                    self.data.left();
+                    return self.edited();
+                }
+                KeyCode::Right => {
+                    self.data.right();
+                    return self.edited();
                 }
                 _ => (),
             }
@@ -61,25 +159,75 @@ impl<T: CrudRow + Send> Component for CrudEdit<T> {
             Action::CrudEdit(mode, idx) => {
                 if mode == self.mode {
                     self.data.edit(idx).await?;
+                    self.saved = self.data.values();
+                    self.validating = false;
                     self.focused = true;
                 }
             }
             Action::CrudNew(mode) => {
                 if mode == self.mode {
-                    self.data.new();
+                    self.data.new().await?;
+                    self.saved = self.data.values();
+                    self.validating = false;
                     self.focused = true;
                 }
             }
             Action::Save => {
+                self.validating = true;
+                self.data.validate()?;
                 self.data.save().await?;
+                self.saved = self.data.values();
                 self.focused = false;
                 return Ok(Some(Action::TabChange(self.mode)));
             }
+            Action::Back if self.has_unsaved_changes() => {
+                return Ok(Some(Action::Confirm(
+                    "Discard unsaved changes?".to_string(),
+                    Box::new(Action::Discard(Box::new(Action::Back))),
+                )));
+            }
+            Action::Discard(_) => self.saved = self.data.values(),
+            Action::TabChange(_) => self.focused = false,
             Action::Back => {
                 self.focused = false;
                 return Ok(Some(Action::TabChange(self.mode)));
             }
             Action::Tab => self.data.focus_next_field(),
+            Action::Undo | Action::Redo => {
+                if let Some(input) = self.data.focused_input() {
+                    let changed = if action == Action::Undo {
+                        input.undo()
+                    } else {
+                        input.redo()
+                    };
+                    if changed {
+                        return self.edited();
+                    }
+                }
+            }
+            Action::TogglePreview => {
+                self.data.toggle_preview();
+                return Ok(Some(Action::Render));
+            }
+            Action::EditExternally => {
+                return Ok(Some(Action::OpenEditor(self.write_focused_field()?)));
+            }
+            Action::EditorClosed(_) => {
+                self.read_focused_field()?;
+                return Ok(Some(Action::Render));
+            }
+            Action::EditorFailed(message) => {
+                self.editing = None;
+                return Err(eyre!(message));
+            }
+            Action::Up => {
+                self.data.up();
+                return Ok(Some(Action::Render));
+            }
+            Action::Down => {
+                self.data.down();
+                return Ok(Some(Action::Render));
+            }
             _ => (),
         }
         Ok(None)
@@ -89,8 +237,24 @@ impl<T: CrudRow + Send> Component for CrudEdit<T> {
         self.focused
     }
 
+    fn has_unsaved_changes(&self) -> bool {
+        self.focused && self.data.values() != self.saved
+    }
+
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        self.data.draw(f, area)
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+        let mut title = match self.data.id() {
+            Some(id) => format!("Edit {} #{}", self.mode, id),
+            None => format!("New {}", self.mode),
+        };
+        if self.has_unsaved_changes() {
+            title.push_str(" [modified]");
+        }
+        f.render_widget(Paragraph::new(title).style(TableStyle::header()), layout[0]);
+        self.data.draw(f, layout[1])
     }
 
     fn component_type(&self) -> Area {
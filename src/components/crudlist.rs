@@ -1,4 +1,7 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
 use async_trait::async_trait;
 use blogapi::models::_entities::posts::{ActiveModel, Entity, Model};
@@ -7,26 +10,30 @@ use blogapi::models::_entities::users::{
 };
 use color_eyre::eyre::{self, eyre, Result};
 use color_eyre::owo_colors::OwoColorize;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::style::{Color, Stylize};
 use ratatui::widgets::{Row, Table, TableState};
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     prelude::Rect,
     style::Style,
-    widgets::{Block, Borders, List, ListState, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListState, Paragraph, Wrap},
+};
+use sea_orm::{
+    ActiveModelTrait, Database, DatabaseConnection, EntityTrait, ModelTrait, Order, Set,
 };
-use sea_orm::{ActiveModelTrait, Database, DatabaseConnection, EntityTrait, ModelTrait, Set};
 use serde::Serializer;
 
 use super::Component;
 use crate::style::{FormStyle, TableStyle};
 use crate::{
-    action::Action,
+    action::{Action, Bulk},
     area::Area,
-    data::CrudData,
+    config::Config,
+    data::{count, CrudData},
     mode::{CrudMode, Mode},
-    tui::{self, Frame},
+    tui::{self, Event, Frame},
 };
 
 #[derive(Default)]
@@ -38,6 +45,18 @@ pub struct CrudList<'a, T: CrudData + Send> {
     table: Table<'a>,
     test: Box<String>,
     table_state: TableState,
+    filter: String,
+    filtering: bool,
+    filtered: Vec<usize>,
+    sort: Option<(usize, Order)>,
+    page_height: usize,
+    preview: bool,
+    details: bool,
+    /// Whether only the drafts are loaded.
+    drafts_only: bool,
+    /// Database ids of the marked rows, which bulk actions apply to. Cleared when the filter
+    /// or the drafts view changes, so that no row hidden by them stays marked.
+    marked: HashSet<i32>,
 }
 
 impl<T: CrudData + Default> CrudList<'_, T> {
@@ -51,13 +70,50 @@ impl<T: CrudData + Default> CrudList<'_, T> {
 
     async fn populate_table(&mut self) -> Result<()> {
         self.data.refresh().await?;
-        let header = Row::new(self.data.headers()).style(TableStyle::header());
-        let rows: Vec<Row> = self
+        self.table_state.select(Some(0));
+        self.build_table();
+        return Ok(());
+    }
+
+    /// Rebuild the table from the rows matching the filter, keeping the selection in range.
+    fn build_table(&mut self) {
+        let rows = self.data.rows();
+        let filter = self.filter.to_lowercase();
+        self.filtered = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                filter.is_empty() || row.iter().any(|x| x.to_lowercase().contains(&filter))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let headers: Vec<String> = self
             .data
-            .rows()
+            .headers()
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| match &self.sort {
+                Some((column, Order::Desc)) if *column == i => format!("{} ▼", x),
+                Some((column, _)) if *column == i => format!("{} ▲", x),
+                _ => x,
+            })
+            .collect();
+        let header = Row::new(headers).style(TableStyle::header());
+        let rows: Vec<Row> = self
+            .filtered
             .iter()
-            .cloned()
-            .map(|x| Row::new(x))
+            .map(|i| {
+                let mut cells = rows[*i].clone();
+                let mut style = self.data.row_style(*i);
+                if self.marked.contains(&self.data.to_db_id(*i)) {
+                    if let Some(first) = cells.first_mut() {
+                        first.insert_str(0, "* ");
+                    }
+                    style = style.patch(TableStyle::marked());
+                }
+                Row::new(cells).style(style)
+            })
             .collect();
         let widths = self.data.widths();
         self.table = Table::new(rows, widths)
@@ -65,46 +121,184 @@ impl<T: CrudData + Default> CrudList<'_, T> {
             .highlight_style(TableStyle::highlighted())
             .header(header);
 
-        if self.data.num_rows() > 0 {
-            self.table_state.select(Some(0));
+        if self.filtered.is_empty() {
+            self.table_state.select(None);
+        } else {
+            let last = self.filtered.len() - 1;
+            let i = self.table_state.selected().unwrap_or(0).min(last);
+            self.table_state.select(Some(i));
         }
-
-        return Ok(());
     }
 
-    async fn delete_selected_post(&mut self) -> Result<()> {
-        if let Some(idx) = self.table_state.selected() {
-            self.data.delete(idx).await?;
+    /// Load pages until at least `len` rows match the filter or every row is loaded.
+    async fn load_until(&mut self, len: usize) -> Result<()> {
+        while self.filtered.len() < len
+            && self.data.first_row() + self.data.num_rows() < self.data.total_rows()
+        {
+            if !self.data.load_more().await? {
+                break;
+            }
+            self.build_table();
         }
-        return Ok(());
+        Ok(())
     }
 
-    fn select_next(&mut self) {
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i < self.data.num_rows() - 1 {
-                    i + 1
-                } else {
-                    i
-                }
+    /// Load the pages before the loaded rows until at least `len` more rows match the filter
+    /// or the first row is loaded, keeping the same row selected.
+    async fn load_before(&mut self, len: usize) -> Result<()> {
+        let mut added = 0;
+        while added < len && self.data.first_row() > 0 {
+            let shown = self.filtered.len();
+            if self.data.load_previous().await? == 0 {
+                break;
+            }
+            self.build_table();
+            let inserted = self.filtered.len() - shown;
+            if let Some(i) = self.table_state.selected() {
+                self.table_state.select(Some(i + inserted));
             }
-            None => 0,
+            *self.table_state.offset_mut() += inserted;
+            added += inserted;
+        }
+        Ok(())
+    }
+
+    /// Cycle `column` through ascending, descending and default order.
+    async fn sort_by(&mut self, column: usize) -> Result<()> {
+        let order = match &self.sort {
+            Some((current, Order::Asc)) if *current == column => Some(Order::Desc),
+            Some((current, Order::Desc)) if *current == column => None,
+            _ => Some(Order::Asc),
         };
-        self.table_state.select(Some(i));
+        if self.data.set_order(column, order.clone()) {
+            self.sort = order.map(|x| (column, x));
+            self.populate_table().await?;
+        }
+        Ok(())
     }
 
-    fn select_prev(&mut self) {
-        let i = match self.table_state.selected() {
-            Some(i) => {
-                if i > 0 {
-                    i - 1
-                } else {
-                    0
-                }
+    /// Index in `data` of the selected row.
+    fn selected(&self) -> Option<usize> {
+        self.table_state
+            .selected()
+            .and_then(|x| self.filtered.get(x))
+            .copied()
+    }
+
+    /// Mark the selected row, or unmark it if `toggle` and it is marked already.
+    fn mark_selected(&mut self, toggle: bool) {
+        if let Some(idx) = self.selected() {
+            let id = self.data.to_db_id(idx);
+            if !self.marked.insert(id) && toggle {
+                self.marked.remove(&id);
             }
-            None => 0,
+        }
+    }
+
+    /// Mark every row matching the filter, loading them all first, or unmark them if they
+    /// are all marked already.
+    async fn mark_all(&mut self) -> Result<()> {
+        self.load_before(usize::MAX).await?;
+        self.load_until(usize::MAX).await?;
+        let ids: Vec<i32> = self
+            .filtered
+            .iter()
+            .map(|x| self.data.to_db_id(*x))
+            .collect();
+        if ids.iter().all(|x| self.marked.contains(x)) {
+            for id in ids {
+                self.marked.remove(&id);
+            }
+        } else {
+            self.marked.extend(ids);
+        }
+        Ok(())
+    }
+
+    /// Database ids of the rows a bulk action applies to, the marked ones or else the
+    /// selected one, and how to name them.
+    async fn targets(&self) -> Result<(Vec<i32>, String)> {
+        if !self.marked.is_empty() {
+            let ids = self.marked.iter().copied().collect();
+            return Ok((ids, count(self.marked.len() as u64, "marked row")));
+        }
+        match self.selected() {
+            Some(idx) => Ok((
+                vec![self.data.to_db_id(idx)],
+                self.data.describe(idx).await?,
+            )),
+            None => Err(eyre!("No row is selected")),
+        }
+    }
+
+    /// Delete the loaded row with database id `id`.
+    async fn delete_row(&mut self, id: i32) -> Result<()> {
+        if let Some(idx) = (0..self.data.num_rows()).find(|x| self.data.to_db_id(*x) == id) {
+            self.data.delete(idx).await?;
+        }
+        Ok(())
+    }
+
+    /// Move the selection by `delta` rows, loading the next or the previous page ahead of
+    /// time so that scrolling never hits either end of the loaded rows.
+    async fn move_selection(&mut self, delta: isize) -> Result<()> {
+        let page = self.page_height.max(1);
+        let above = delta.min(0).unsigned_abs() + page;
+        let selected = self.table_state.selected().unwrap_or(0);
+        if selected < above {
+            self.load_before(above - selected).await?;
+        }
+        let i = self
+            .table_state
+            .selected()
+            .unwrap_or(0)
+            .saturating_add_signed(delta);
+        self.load_until(i.saturating_add(page + 1)).await?;
+        if let Some(last) = self.filtered.len().checked_sub(1) {
+            self.table_state.select(Some(i.min(last)));
+        }
+        Ok(())
+    }
+
+    /// The rows shown out of the total, after the filter prompt if there is one.
+    fn status_line(&self) -> String {
+        let shown = self.filtered.len();
+        let first = self.table_state.offset().min(shown);
+        let last = (first + self.page_height).min(shown);
+        // Without a filter, every row is shown from the first loaded one on.
+        let skipped = if self.filter.is_empty() {
+            self.data.first_row()
+        } else {
+            0
+        };
+        let rows = if shown == 0 {
+            "no rows".to_string()
+        } else {
+            format!("rows {}–{}", skipped + first + 1, skipped + last)
+        };
+        let rows = if self.drafts_only {
+            format!("drafts, {}", rows)
+        } else {
+            rows
         };
-        self.table_state.select(Some(i));
+        let rows = if self.marked.is_empty() {
+            rows
+        } else {
+            format!("{} marked, {}", self.marked.len(), rows)
+        };
+        if !self.filtering && self.filter.is_empty() {
+            return format!("{} of {}", rows, self.data.total_rows());
+        }
+        let cursor = if self.filtering { "_" } else { "" };
+        format!(
+            "/{}{}  {} of {} matching ({} of {} loaded)",
+            self.filter,
+            cursor,
+            rows,
+            shown,
+            self.data.num_rows(),
+            self.data.total_rows()
+        )
     }
 }
 
@@ -116,7 +310,70 @@ impl<'a, T: CrudData + Default + Send> Component for CrudList<'a, T> {
         Ok(())
     }
 
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.data.set_config(config);
+        Ok(())
+    }
+
+    fn handle_events(&mut self, event: Option<Event>) -> Result<Option<Action>> {
+        match event {
+            Some(Event::Paste(text)) if self.focused && self.filtering => {
+                self.filter.extend(text.chars().filter(|c| !c.is_control()));
+                self.marked.clear();
+                self.table_state.select(Some(0));
+                self.build_table();
+                Ok(Some(Action::Render))
+            }
+            Some(Event::Key(key)) => self.handle_key_events(key),
+            _ => Ok(None),
+        }
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if !self.focused || !self.filtering || key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(None);
+        }
+        match key.code {
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.marked.clear();
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.marked.clear();
+            }
+            KeyCode::Enter => self.filtering = false,
+            _ => return Ok(None),
+        }
+        self.table_state.select(Some(0));
+        self.build_table();
+        Ok(Some(Action::Render))
+    }
+
     async fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        // While typing a filter, keys bound to actions are part of the filter instead.
+        if self.filtering
+            && matches!(
+                action,
+                Action::Edit
+                    | Action::New
+                    | Action::Delete
+                    | Action::Filter
+                    | Action::Sort(_)
+                    | Action::History
+                    | Action::Publish
+                    | Action::Unpublish
+                    | Action::DraftsOnly
+                    | Action::ToggleDetails
+                    | Action::Mark
+                    | Action::MarkAll
+                    | Action::BulkTag
+                    | Action::BulkAuthor
+                    | Action::BulkStatus
+            )
+        {
+            return Ok(None);
+        }
         match action {
             Action::TabChange(newmode) => {
                 self.focused = newmode == self.mode;
@@ -125,8 +382,26 @@ impl<'a, T: CrudData + Default + Send> Component for CrudList<'a, T> {
                     return Ok(Some(Action::Render));
                 }
             }
+            Action::Delete if !self.marked.is_empty() => {
+                let (ids, _) = self.targets().await?;
+                let rows = self.data.describe_bulk(&ids).await?;
+                return Ok(Some(Action::Confirm(
+                    format!("Delete {}?", rows),
+                    Box::new(Action::BulkEdit(Bulk::Delete)),
+                )));
+            }
             Action::Delete => {
-                self.delete_selected_post().await?;
+                if let Some(idx) = self.selected() {
+                    let message = format!("Delete {}?", self.data.describe(idx).await?);
+                    let id = self.data.to_db_id(idx);
+                    return Ok(Some(Action::Confirm(
+                        message,
+                        Box::new(Action::DeleteRow(id)),
+                    )));
+                }
+            }
+            Action::DeleteRow(id) => {
+                self.delete_row(id).await?;
                 self.populate_table().await?;
                 return Ok(Some(Action::Render));
             }
@@ -135,26 +410,203 @@ impl<'a, T: CrudData + Default + Send> Component for CrudList<'a, T> {
                 return Ok(Some(Action::CrudNew(self.mode)));
             }
             Action::Edit => {
-                if let Some(idx) = self.table_state.selected() {
+                if let Some(idx) = self.selected() {
                     self.focused = false;
                     return Ok(Some(Action::CrudEdit(self.mode, self.data.to_db_id(idx))));
                 }
             }
+            Action::History if self.mode == Mode::Posts => {
+                if let Some(idx) = self.selected() {
+                    self.focused = false;
+                    return Ok(Some(Action::ShowHistory(self.data.to_db_id(idx))));
+                }
+            }
+            Action::Publish | Action::Unpublish => {
+                if let Some(idx) = self.selected() {
+                    self.data.publish(idx, action == Action::Publish).await?;
+                    self.build_table();
+                    return Ok(Some(Action::Render));
+                }
+            }
+            Action::Mark => {
+                self.mark_selected(true);
+                self.build_table();
+                return Ok(Some(Action::Render));
+            }
+            Action::MarkUp | Action::MarkDown => {
+                // Extend the marks to the row moved to, as shift-arrows extend a selection.
+                self.mark_selected(false);
+                let delta = if action == Action::MarkUp { -1 } else { 1 };
+                self.move_selection(delta).await?;
+                self.mark_selected(false);
+                self.build_table();
+                return Ok(Some(Action::Render));
+            }
+            Action::MarkAll => {
+                self.mark_all().await?;
+                self.build_table();
+                return Ok(Some(Action::Render));
+            }
+            Action::BulkTag | Action::BulkAuthor | Action::BulkStatus => {
+                let (_, rows) = self.targets().await?;
+                let choices = self
+                    .data
+                    .bulk_choices(&action)
+                    .await?
+                    .into_iter()
+                    .map(|(label, change)| {
+                        let message = match &change {
+                            Bulk::Tag(_) => format!("Tag {} with \"{}\"?", rows, label),
+                            Bulk::Author(_) => format!("Set the author of {} to {}?", rows, label),
+                            Bulk::Status(_) => format!("Set {} to {}?", rows, label),
+                            Bulk::Delete => format!("Delete {}?", rows),
+                        };
+                        let action = Action::Confirm(message, Box::new(Action::BulkEdit(change)));
+                        (label, action)
+                    })
+                    .collect();
+                let title = match action {
+                    Action::BulkTag => "Add tag",
+                    Action::BulkAuthor => "Set author",
+                    _ => "Set status",
+                };
+                return Ok(Some(Action::Choose(title.to_string(), choices)));
+            }
+            Action::BulkEdit(change) => {
+                let (ids, _) = self.targets().await?;
+                let summary = self.data.bulk(&ids, &change).await?;
+                if change == Bulk::Delete {
+                    self.marked.clear();
+                }
+                self.populate_table().await?;
+                return Ok(Some(Action::Info(summary)));
+            }
+            Action::DraftsOnly => {
+                if self.data.set_drafts_only(!self.drafts_only) {
+                    self.drafts_only = !self.drafts_only;
+                    self.marked.clear();
+                    self.populate_table().await?;
+                    return Ok(Some(Action::Render));
+                }
+            }
             Action::Up => {
-                self.select_prev();
+                self.move_selection(-1).await?;
                 return Ok(Some(Action::Render));
             }
             Action::Down => {
-                self.select_next();
+                self.move_selection(1).await?;
+                return Ok(Some(Action::Render));
+            }
+            Action::PageUp => {
+                let page = self.page_height.max(1) as isize;
+                self.move_selection(-page).await?;
+                return Ok(Some(Action::Render));
+            }
+            Action::PageDown => {
+                let page = self.page_height.max(1) as isize;
+                self.move_selection(page).await?;
+                return Ok(Some(Action::Render));
+            }
+            Action::Home => {
+                if self.data.first_row() > 0 {
+                    self.populate_table().await?;
+                } else {
+                    self.table_state.select(Some(0));
+                    self.build_table();
+                }
+                return Ok(Some(Action::Render));
+            }
+            Action::End => {
+                // Load the last page on its own rather than every page before it.
+                if self.data.first_row() + self.data.num_rows() < self.data.total_rows() {
+                    self.data.load_last().await?;
+                    self.build_table();
+                }
+                if !self.filtered.is_empty() {
+                    self.table_state.select(Some(self.filtered.len() - 1));
+                }
+                self.load_before(self.page_height.max(1)).await?;
+                return Ok(Some(Action::Render));
+            }
+            Action::Filter => {
+                self.filtering = true;
+                return Ok(Some(Action::Render));
+            }
+            Action::TogglePreview => {
+                self.preview = !self.preview;
                 return Ok(Some(Action::Render));
             }
+            Action::ToggleDetails => {
+                self.details = !self.details;
+                return Ok(Some(Action::Render));
+            }
+            Action::Sort(column) => {
+                self.sort_by(column).await?;
+                return Ok(Some(Action::Render));
+            }
+            Action::Back => {
+                if self.filtering || !self.filter.is_empty() {
+                    self.filtering = false;
+                    self.filter.clear();
+                    self.marked.clear();
+                    self.table_state.select(Some(0));
+                    self.build_table();
+                    return Ok(Some(Action::Render));
+                }
+                if !self.marked.is_empty() {
+                    self.marked.clear();
+                    self.build_table();
+                    return Ok(Some(Action::Render));
+                }
+            }
             _ => (),
         }
         Ok(None)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        f.render_stateful_widget(self.table.clone(), area, &mut self.table_state);
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        let mut table_area = layout[0];
+        if self.preview {
+            if let Some(text) = self.selected().and_then(|x| self.data.preview(x)) {
+                let halves = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(table_area);
+                table_area = halves[0];
+                f.render_widget(
+                    Paragraph::new(text)
+                        .wrap(Wrap { trim: false })
+                        .block(Block::default().borders(Borders::LEFT).title("Preview")),
+                    halves[1],
+                );
+            }
+        }
+        if self.details {
+            if let Some(idx) = self.selected() {
+                let halves = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(table_area);
+                table_area = halves[0];
+                f.render_widget(
+                    Paragraph::new(details(self.data.details(idx)))
+                        .wrap(Wrap { trim: false })
+                        .block(Block::default().borders(Borders::TOP).title("Details")),
+                    halves[1],
+                );
+            }
+        }
+        // One line of the table area goes to the header.
+        self.page_height = usize::from(table_area.height).saturating_sub(1);
+        f.render_stateful_widget(self.table.clone(), table_area, &mut self.table_state);
+        f.render_widget(
+            Paragraph::new(self.status_line()).style(TableStyle::header()),
+            layout[1],
+        );
         Ok(())
     }
 
@@ -166,3 +618,23 @@ impl<'a, T: CrudData + Default + Send> Component for CrudList<'a, T> {
         self.focused
     }
 }
+
+/// `fields` one per line, with their values aligned after the longest label.
+fn details(fields: Vec<(String, String)>) -> Vec<Line<'static>> {
+    let width = fields
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for (label, value) in fields {
+        let mut values = value.lines();
+        let first = values.next().unwrap_or_default().to_string();
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:width$}  ", label), TableStyle::header()),
+            Span::raw(first),
+        ]));
+        lines.extend(values.map(|x| Line::from(format!("{:width$}  {}", "", x))));
+    }
+    lines
+}
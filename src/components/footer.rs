@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
 use color_eyre::eyre::Result;
 use ratatui::{prelude::*, widgets::*};
@@ -11,10 +13,15 @@ use crate::{
     tui::Frame,
 };
 
+/// How long an error or a summary stays in place of the keybindings.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Default)]
 pub struct Footer {
     mode: Mode,
     config: Config,
+    /// Error or summary shown, in its color, and when it was sent.
+    message: Option<(String, Color, Instant)>,
 }
 
 impl Footer {
@@ -53,13 +60,42 @@ impl Component for Footer {
     }
 
     async fn update(&mut self, action: Action) -> Result<Option<Action>> {
-        if let Action::TabChange(newmode) = action {
-            self.mode = newmode;
+        match action {
+            Action::TabChange(newmode) => self.mode = newmode,
+            Action::Error(message) => {
+                log::error!("{}", message);
+                let message = format!("Error: {}", message);
+                self.message = Some((message, Color::Red, Instant::now()));
+                return Ok(Some(Action::Render));
+            }
+            Action::Info(message) => {
+                log::info!("{}", message);
+                self.message = Some((message, Color::Green, Instant::now()));
+                return Ok(Some(Action::Render));
+            }
+            Action::Tick => {
+                if let Some((_, _, since)) = &self.message {
+                    if since.elapsed() >= MESSAGE_TIMEOUT {
+                        self.message = None;
+                        return Ok(Some(Action::Render));
+                    }
+                }
+            }
+            _ => (),
         }
         Ok(None)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if let Some((message, color, _)) = &self.message {
+            f.render_widget(
+                Paragraph::new(message.replace('\n', " "))
+                    .block(Block::new().borders(Borders::TOP))
+                    .fg(*color),
+                area,
+            );
+            return Ok(());
+        }
         f.render_widget(
             Paragraph::new(self.get_keybindings())
                 .block(Block::new().borders(Borders::TOP))
@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use blogapi::models::_entities::posts::{Entity as PostEntity, Model as Post};
+use color_eyre::eyre::{eyre, Result};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState},
+};
+use sea_orm::{DatabaseConnection, EntityTrait};
+
+use super::Component;
+use crate::{
+    action::Action,
+    area::Area,
+    config::Config,
+    data::{
+        local_time,
+        revisions::{self, Diff, Revision},
+    },
+    mode::Mode,
+    style::{DiffStyle, TableStyle},
+    tui::Frame,
+};
+
+/// The revisions of a post, with the changes from the selected one to the current version.
+///
+/// It opens on [`Action::ShowHistory`] and goes back to the posts on [`Action::Back`].
+/// [`Action::Restore`] puts the post back as it was in the selected revision, once confirmed.
+#[derive(Default)]
+pub struct History {
+    db: Option<DatabaseConnection>,
+    post: Option<Post>,
+    revisions: Vec<(Revision, String)>,
+    table_state: TableState,
+    /// First line of the diff shown.
+    scroll: u16,
+    time_format: String,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    async fn load(&mut self, post_id: i32) -> Result<()> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        self.post = PostEntity::find_by_id(post_id).one(cnx).await?;
+        self.revisions = revisions::list(cnx, post_id).await?;
+        self.table_state
+            .select((!self.revisions.is_empty()).then_some(0));
+        self.scroll = 0;
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&Revision> {
+        self.table_state
+            .selected()
+            .and_then(|x| self.revisions.get(x))
+            .map(|(revision, _)| revision)
+    }
+
+    /// When `revision` was saved, in the local time zone.
+    fn date(&self, revision: &Revision) -> String {
+        local_time(revision.updated_at, &self.time_format)
+    }
+
+    /// Scroll the diff by `delta` lines, keeping its last line in view.
+    fn scroll_by(&mut self, delta: i16) {
+        let last = self.diff().len().saturating_sub(1).min(u16::MAX as usize) as u16;
+        self.scroll = self.scroll.saturating_add_signed(delta).min(last);
+    }
+
+    fn select(&mut self, delta: isize) {
+        if let Some(i) = self.table_state.selected() {
+            let i = i.saturating_add_signed(delta).min(self.revisions.len() - 1);
+            self.table_state.select(Some(i));
+            self.scroll = 0;
+        }
+    }
+
+    /// Changes from the selected revision to the current version, field by field.
+    fn diff(&self) -> Vec<Line<'static>> {
+        let (Some(post), Some(revision)) = (&self.post, self.selected()) else {
+            return Vec::new();
+        };
+        let fields = [
+            ("Title", revision.title.as_str(), post.title.as_str()),
+            (
+                "Summary",
+                revision.summary.as_deref().unwrap_or_default(),
+                post.summary.as_deref().unwrap_or_default(),
+            ),
+            (
+                "Content",
+                revision.content.as_deref().unwrap_or_default(),
+                post.content.as_deref().unwrap_or_default(),
+            ),
+        ];
+        let mut lines = Vec::new();
+        for (name, old, new) in fields {
+            lines.push(Line::from(Span::styled(name, TableStyle::header())));
+            for line in revisions::diff(old, new) {
+                lines.push(match line {
+                    Diff::Same(x) => Line::from(format!("  {}", x)),
+                    Diff::Removed(x) => Line::styled(format!("- {}", x), DiffStyle::removed()),
+                    Diff::Added(x) => Line::styled(format!("+ {}", x), DiffStyle::added()),
+                });
+            }
+            lines.push(Line::default());
+        }
+        lines
+    }
+}
+
+#[async_trait]
+impl Component for History {
+    fn register_db_handler(&mut self, db: Option<DatabaseConnection>) -> Result<()> {
+        self.db = db;
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.time_format = config.time_format().to_string();
+        Ok(())
+    }
+
+    async fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::ShowHistory(post_id) => {
+                self.load(post_id).await?;
+                return Ok(Some(Action::Render));
+            }
+            Action::TabChange(_) => self.post = None,
+            Action::Back => {
+                self.post = None;
+                return Ok(Some(Action::TabChange(Mode::Posts)));
+            }
+            Action::Up => self.select(-1),
+            Action::Down => self.select(1),
+            Action::PageUp => self.scroll_by(-10),
+            Action::PageDown => self.scroll_by(10),
+            Action::Restore => {
+                if let Some(revision) = self.selected() {
+                    let message = format!("Restore the post as it was on {}?", self.date(revision));
+                    return Ok(Some(Action::Confirm(
+                        message,
+                        Box::new(Action::RestoreRevision(revision.id)),
+                    )));
+                }
+            }
+            Action::RestoreRevision(id) => {
+                let cnx = self
+                    .db
+                    .as_ref()
+                    .ok_or_else(|| eyre!("Database is not connected"))?;
+                let post = revisions::restore(cnx, id).await?;
+                self.load(post.id).await?;
+            }
+            _ => return Ok(None),
+        }
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(area);
+        let title = match &self.post {
+            Some(post) => format!("History of \"{}\"", post.title),
+            None => "History".to_string(),
+        };
+        let dates: Vec<String> = self.revisions.iter().map(|(x, _)| self.date(x)).collect();
+        let date_width = dates.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+        let rows: Vec<Row> = self
+            .revisions
+            .iter()
+            .zip(dates)
+            .map(|((revision, author), date)| {
+                Row::new(vec![date, author.clone(), revision.title.clone()])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(date_width.max(4) as u16),
+                Constraint::Percentage(30),
+                Constraint::Percentage(70),
+            ],
+        )
+        .header(Row::new(vec!["Date", "Author", "Title"]).style(TableStyle::header()))
+        .block(Block::default().borders(Borders::RIGHT).title(title))
+        .highlight_style(TableStyle::highlighted());
+        f.render_stateful_widget(table, layout[0], &mut self.table_state);
+
+        let diff = if self.revisions.is_empty() {
+            vec![Line::from("This post has no revisions yet.")]
+        } else {
+            self.diff()
+        };
+        f.render_widget(
+            Paragraph::new(diff)
+                .scroll((self.scroll, 0))
+                .block(Block::default().title("Changes to the current version")),
+            layout[1],
+        );
+        Ok(())
+    }
+
+    fn focused(&self) -> bool {
+        self.post.is_some()
+    }
+
+    fn component_type(&self) -> Area {
+        Area::Main
+    }
+}
@@ -13,7 +13,7 @@ impl TabBar<'_> {
     pub fn new(curmode: Mode) -> Self {
         TabBar {
             tabbar: Tabs::new(
-                (0..3)
+                (0..4)
                     .map(|x| Mode::try_from(x).unwrap().to_string())
                     .collect(),
             )
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, fmt, path::PathBuf};
 
+use chrono::format::{Item, StrftimeItems};
 use color_eyre::eyre::Result;
 use config::Value;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -15,6 +16,8 @@ use crate::{action::Action, mode::Mode};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct AppConfig {
     #[serde(default)]
@@ -33,6 +36,13 @@ pub struct Config {
     pub styles: Styles,
     #[serde(default)]
     pub db: String,
+    /// Email of the user new posts are attributed to by default.
+    #[serde(default)]
+    pub acting_admin: String,
+    /// How dates are shown, in local time, as understood by `chrono`. Defaults to
+    /// [`DEFAULT_TIME_FORMAT`].
+    #[serde(default)]
+    pub time_format: String,
 }
 
 impl Config {
@@ -67,6 +77,12 @@ impl Config {
         }
 
         let mut cfg: Self = builder.build()?.try_deserialize()?;
+        if !is_valid_time_format(&cfg.time_format) {
+            return Err(config::ConfigError::Message(format!(
+                "Invalid time_format \"{}\"",
+                cfg.time_format
+            )));
+        }
 
         for (mode, default_bindings) in default_config.keybindings.iter() {
             let user_bindings = cfg.keybindings.entry(*mode).or_default();
@@ -87,6 +103,20 @@ impl Config {
 
         Ok(cfg)
     }
+
+    pub fn time_format(&self) -> &str {
+        if self.time_format.is_empty() {
+            DEFAULT_TIME_FORMAT
+        } else {
+            &self.time_format
+        }
+    }
+}
+
+/// Whether `chrono` understands every specifier of `format`, as it panics when writing a date
+/// with the others.
+fn is_valid_time_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|x| x != Item::Error)
 }
 
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
@@ -419,6 +449,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::data::{posts::Posts, CrudData};
 
     #[test]
     fn test_parse_style_default() {
@@ -461,6 +492,13 @@ mod tests {
         assert_eq!(color, Some(Color::Indexed(expected)));
     }
 
+    #[test]
+    fn test_time_format() {
+        assert!(is_valid_time_format(DEFAULT_TIME_FORMAT));
+        assert!(is_valid_time_format("%d/%m/%Y %I:%M %p"));
+        assert!(!is_valid_time_format("%Y-%m-%d %Q"));
+    }
+
     #[test]
     fn test_parse_color_unknown() {
         let color = parse_color("unknown");
@@ -481,6 +519,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_sort_keybindings() -> Result<()> {
+        let c = Config::new()?;
+        let columns: Vec<usize> = c
+            .keybindings
+            .get(&Mode::Posts)
+            .unwrap()
+            .values()
+            .filter_map(|x| match x {
+                Action::Sort(column) => Some(*column),
+                _ => None,
+            })
+            .collect();
+        assert!(!columns.is_empty());
+        let mut posts = Posts::default();
+        for column in columns {
+            assert!(
+                posts.set_order(column, None),
+                "column {} cannot be sorted",
+                column
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
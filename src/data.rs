@@ -1,24 +1,154 @@
 use async_trait::async_trait;
-use color_eyre::Result;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use color_eyre::{eyre::eyre, Result};
 use ratatui::{
     layout::{Constraint, Rect},
+    style::Style,
+    text::Text,
     Frame,
 };
-use sea_orm::DatabaseConnection;
+use sea_orm::{DatabaseConnection, Order};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    action::{Action, Bulk},
+    config::Config,
+    widgets::input::Input,
+};
 
 pub mod posts;
+pub mod publications;
+pub mod revisions;
+pub mod schedule;
+pub mod slugs;
+pub mod tags;
 pub mod users;
+pub mod validation;
+
+/// Number of rows fetched at once by [`CrudData::load_more`].
+pub const PAGE_SIZE: u64 = 100;
 
 #[async_trait]
-pub trait CrudData: Default + Send {
+pub trait CrudData: Default + Send + Sync {
     fn headers(&self) -> Vec<String>;
     fn rows(&self) -> Vec<Vec<String>>;
     fn widths(&self) -> Vec<Constraint>;
     fn num_rows(&self) -> usize;
     fn set_db(&mut self, cnx: Option<DatabaseConnection>);
     async fn delete(&self, idx: usize) -> Result<()>;
+    /// What deleting the row at `idx` removes, as shown in the confirmation prompt.
+    async fn describe(&self, idx: usize) -> Result<String>;
+    /// What deleting the rows with database ids `ids` removes, as shown in the confirmation
+    /// prompt.
+    async fn describe_bulk(&self, ids: &[i32]) -> Result<String> {
+        Ok(count(ids.len() as u64, "row"))
+    }
+    /// Drop the loaded rows and load the first page again.
     async fn refresh(&mut self) -> Result<()>;
+    /// Append the next page of rows. Returns `false` once every row is loaded.
+    async fn load_more(&mut self) -> Result<bool>;
+    /// Drop the loaded rows and load the last page only, without the pages before it.
+    async fn load_last(&mut self) -> Result<()>;
+    /// Insert the page before the loaded rows in front of them. Returns the number of rows
+    /// inserted, 0 once the first row is loaded.
+    async fn load_previous(&mut self) -> Result<usize>;
+    /// Drop the loaded rows and load only the one with database id `id`. Returns `false` if
+    /// there is none. Call `refresh` before loading pages again.
+    async fn load_id(&mut self, id: i32) -> Result<bool>;
+    /// Position of the first loaded row among all the rows, past 0 after `load_last`.
+    fn first_row(&self) -> usize;
+    /// Number of rows in the database, including those not loaded yet.
+    fn total_rows(&self) -> usize;
+    /// Order the next `refresh` by `column`, or by id when `order` is `None`. Returns `false`
+    /// if the column cannot be sorted on.
+    fn set_order(&mut self, column: usize, order: Option<Order>) -> bool;
     fn to_db_id(&self, idx: usize) -> i32;
+    fn record(&self, idx: usize) -> Result<JsonValue>;
+    /// Every field of the row at `idx`, as label and value pairs. Defaults to the fields of
+    /// its [`record`](CrudData::record).
+    fn details(&self, idx: usize) -> Vec<(String, String)> {
+        let Ok(JsonValue::Object(fields)) = self.record(idx) else {
+            return Vec::new();
+        };
+        fields
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    JsonValue::String(x) => x,
+                    JsonValue::Null => String::new(),
+                    x => x.to_string(),
+                };
+                (label(&key), value)
+            })
+            .collect()
+    }
+    /// Rendered body of the row at `idx`, for the rows that have one.
+    fn preview(&self, idx: usize) -> Option<Text<'static>> {
+        None
+    }
+    /// Style of the row at `idx`, to set apart the rows that need attention.
+    fn row_style(&self, idx: usize) -> Style {
+        Style::default()
+    }
+    /// Publish the row at `idx` now, or take it back to a draft, for the rows that have a
+    /// publication status.
+    async fn publish(&mut self, idx: usize, publish: bool) -> Result<()> {
+        Err(eyre!("These rows cannot be published"))
+    }
+    /// Load only the drafts on the next `refresh`, or every row again. Returns `false` if the
+    /// rows have no drafts.
+    fn set_drafts_only(&mut self, drafts_only: bool) -> bool {
+        false
+    }
+    /// What the marked rows can be changed to with `action`, one of [`Action::BulkTag`],
+    /// [`Action::BulkAuthor`] and [`Action::BulkStatus`], labelled.
+    async fn bulk_choices(&self, action: &Action) -> Result<Vec<(String, Bulk)>> {
+        Err(eyre!("These rows cannot be changed in bulk"))
+    }
+    /// Make `change` to the rows with database ids `ids`, in one transaction. Returns a
+    /// summary of what was done.
+    async fn bulk(&mut self, ids: &[i32], change: &Bulk) -> Result<String> {
+        Err(eyre!("These rows cannot be changed in bulk"))
+    }
+    fn set_config(&mut self, config: Config) {}
+}
+
+/// `key` of a record as a label, `created_at` giving `Created at`.
+fn label(key: &str) -> String {
+    let key = key.replace('_', " ");
+    let mut chars = key.chars();
+    chars
+        .next()
+        .map(|x| x.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// `time`, stored in UTC, in the local time zone and written in `format`.
+pub fn local_time(time: NaiveDateTime, format: &str) -> String {
+    Local.from_utc_datetime(&time).format(format).to_string()
+}
+
+/// `time`, stored in UTC, in the local time zone, as dates are shown and typed.
+fn to_local(time: NaiveDateTime) -> NaiveDateTime {
+    Local.from_utc_datetime(&time).naive_local()
+}
+
+/// `time`, in the local time zone, in UTC to be stored. `None` for the times skipped when
+/// the clocks go forward.
+fn from_local(time: NaiveDateTime) -> Option<NaiveDateTime> {
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|x| x.naive_utc())
+}
+
+/// `count` followed by `noun`, in the plural unless there is exactly one.
+pub fn count(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
 }
 
 #[derive(Default)]
@@ -31,11 +161,43 @@ enum CrudEditMode {
 #[async_trait]
 pub trait CrudRow: Default + Send {
     async fn edit(&mut self, idx: i32) -> Result<()>;
-    fn new(&mut self);
+    async fn new(&mut self) -> Result<()>;
     async fn save(&mut self) -> Result<()>;
+    /// Check every field and keep the errors to show under the invalid ones. Fails with all
+    /// of them, in which case the row must not be saved.
+    fn validate(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn id(&self) -> Option<i32>;
+    /// Value of every field, compared to tell whether the form was modified.
+    fn values(&self) -> Vec<String>;
+    fn set_field(&mut self, name: &str, value: &str) -> Result<()>;
     fn focus_next_field(&mut self);
-    fn input(&mut self, c: char);
-    fn delete_last_char(&mut self);
+    /// The text of the focused field, or `None` if that field is not edited as text. Its
+    /// editing keys are handled by [`CrudEdit`](crate::components::crudedit::CrudEdit).
+    fn focused_input(&mut self) -> Option<&mut Input>;
+    fn toggle_preview(&mut self) {}
+    fn up(&mut self) {}
+    fn down(&mut self) {}
+    fn left(&mut self) {}
+    fn right(&mut self) {}
+    /// Typed char for a focused field that is not edited as text.
+    fn input(&mut self, c: char) {}
+    /// Backspace for a focused field that is not edited as text.
+    fn delete_last_char(&mut self) {}
+    /// Called after each change to the focused field, to update the fields derived from it.
+    fn edited(&mut self) {}
+    /// Insert pasted `text` in the focused field at once.
+    fn paste(&mut self, text: &str) {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text);
+        } else {
+            text.chars()
+                .filter(|c| !c.is_control())
+                .for_each(|c| self.input(c));
+        }
+    }
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()>;
     fn set_db(&mut self, db: Option<DatabaseConnection>);
+    fn set_config(&mut self, config: Config) {}
 }
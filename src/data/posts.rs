@@ -1,38 +1,78 @@
-use std::{collections::HashMap, fmt::Display, iter::MapWhile, ops::Range};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
 use async_trait::async_trait;
 use blogapi::models::_entities::posts::{
-    ActiveModel as ActivePost, Entity as PostEntity, Model as Post,
+    ActiveModel as ActivePost, Column as PostColumn, Entity as PostEntity, Model as Post,
+    Relation as PostRelation,
+};
+use blogapi::models::_entities::posts_tags::{
+    ActiveModel as ActivePostTag, Column as PostTagColumn, Entity as PostTagEntity,
+};
+use blogapi::models::_entities::tags::{
+    ActiveModel as ActiveTag, Column as TagColumn, Entity as TagEntity,
+};
+use blogapi::models::_entities::users::{
+    Column as UserColumn, Entity as UserEntity, Model as User,
 };
-use blogapi::models::_entities::users::Model as User;
+use chrono::NaiveDateTime;
 use color_eyre::{eyre::eyre, Result};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, Borders, Paragraph},
+    text::Text,
+    widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
 use sea_orm::{
-    ActiveModelBehavior, ActiveModelTrait, DatabaseConnection, EntityTrait, ModelTrait, Set,
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, JoinType, Order, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait, Select, Set, SqlErr, TransactionTrait,
 };
+use serde_json::Value as JsonValue;
 
+use crate::action::{Action, Bulk};
 use crate::components::crudedit::CrudEdit;
+use crate::config::Config;
 use crate::style::FormStyle;
+use crate::widgets::{
+    input::Input,
+    markdown,
+    picker::{Picker, PickerItem},
+};
 
-use super::{CrudData, CrudEditMode, CrudRow};
+use super::{
+    count, from_local, local_time,
+    publications::{self, Status},
+    revisions, slugs, to_local,
+    validation::{self, datetime, max_length, required},
+    CrudData, CrudEditMode, CrudRow, PAGE_SIZE,
+};
 
 #[derive(Default, Eq, PartialEq, Hash, Debug, Copy, Clone)]
 enum PostField {
     #[default]
     Title = 0,
+    Slug,
     Summary,
+    Author,
+    Tags,
+    Status,
+    PublishDate,
     Content,
 }
 
 impl PostField {
     fn next(&self) -> Self {
         match *self {
-            PostField::Title => PostField::Summary,
-            PostField::Summary => PostField::Content,
+            PostField::Title => PostField::Slug,
+            PostField::Slug => PostField::Summary,
+            PostField::Summary => PostField::Author,
+            PostField::Author => PostField::Tags,
+            PostField::Tags => PostField::Status,
+            PostField::Status => PostField::PublishDate,
+            PostField::PublishDate => PostField::Content,
             PostField::Content => PostField::Title,
         }
     }
@@ -46,8 +86,13 @@ impl TryFrom<usize> for PostField {
     fn try_from(value: usize) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(PostField::Title),
-            1 => Ok(PostField::Summary),
-            2 => Ok(PostField::Content),
+            1 => Ok(PostField::Slug),
+            2 => Ok(PostField::Summary),
+            3 => Ok(PostField::Author),
+            4 => Ok(PostField::Tags),
+            5 => Ok(PostField::Status),
+            6 => Ok(PostField::PublishDate),
+            7 => Ok(PostField::Content),
             _ => Err(PostFieldError),
         }
     }
@@ -63,14 +108,100 @@ impl Display for PostField {
 pub struct Posts {
     db: Option<DatabaseConnection>,
     posts: Vec<Post>,
+    tags: HashMap<i32, Vec<String>>,
+    /// Names of the authors of the loaded posts, by user id.
+    authors: HashMap<i32, String>,
+    time_format: String,
+    order: Option<(PostColumn, Order)>,
+    drafts_only: bool,
+    /// First loaded page, past 0 after jumping to the end.
+    first: u64,
+    page: u64,
+    total: u64,
+}
+
+impl Posts {
+    fn query(&self) -> Select<PostEntity> {
+        let mut query = PostEntity::find();
+        if self.drafts_only {
+            query = query.filter(publications::has_status(Status::Draft));
+        }
+        if let Some((column, order)) = &self.order {
+            query = match column {
+                // Authors are sorted by name rather than by id.
+                PostColumn::UserId => query
+                    .join(JoinType::LeftJoin, PostRelation::Users.def())
+                    .order_by(UserColumn::Name, order.clone()),
+                _ => query.order_by(*column, order.clone()),
+            };
+        }
+        query.order_by_asc(PostColumn::Id)
+    }
+}
+
+/// Delete the posts `ids`, with their tag links and revisions first. Returns how many were
+/// deleted.
+async fn delete_posts<C: ConnectionTrait>(cnx: &C, ids: &[i32]) -> Result<u64> {
+    PostTagEntity::delete_many()
+        .filter(PostTagColumn::PostId.is_in(ids.iter().copied()))
+        .exec(cnx)
+        .await?;
+    revisions::delete(cnx, ids).await?;
+    Ok(PostEntity::delete_many()
+        .filter(PostColumn::Id.is_in(ids.iter().copied()))
+        .exec(cnx)
+        .await?
+        .rows_affected)
+}
+
+/// Record that `post` changes now, to be saved with the rest of it.
+pub fn touch(post: &mut ActivePost) {
+    post.updated_at = Set(chrono::Utc::now().naive_utc());
+}
+
+/// Add the tag names and the author names of `posts` to `tags` and `authors`.
+async fn load_names(
+    cnx: &DatabaseConnection,
+    posts: &[Post],
+    tags: &mut HashMap<i32, Vec<String>>,
+    authors: &mut HashMap<i32, String>,
+) -> Result<()> {
+    let links = PostTagEntity::find()
+        .filter(PostTagColumn::PostId.is_in(posts.iter().map(|x| x.id)))
+        .all(cnx)
+        .await?;
+    let names: HashMap<i32, String> = TagEntity::find()
+        .filter(TagColumn::Id.is_in(links.iter().map(|x| x.tag_id)))
+        .all(cnx)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x.name))
+        .collect();
+    for link in links {
+        if let Some(name) = names.get(&link.tag_id) {
+            tags.entry(link.post_id).or_default().push(name.clone());
+        }
+    }
+    let users = UserEntity::find()
+        .filter(UserColumn::Id.is_in(posts.iter().map(|x| x.user_id)))
+        .all(cnx)
+        .await?;
+    authors.extend(users.into_iter().map(|x| (x.id, x.name)));
+    Ok(())
 }
 
 #[async_trait]
 impl CrudData for Posts {
     fn headers(&self) -> Vec<String> {
-        (0..3)
-            .map(|x| PostField::try_from(x).unwrap().to_string())
-            .collect()
+        vec![
+            PostField::Title.to_string(),
+            PostField::Summary.to_string(),
+            PostField::Tags.to_string(),
+            PostField::Author.to_string(),
+            "Created".to_string(),
+            "Updated".to_string(),
+            PostField::Status.to_string(),
+        ]
     }
 
     fn rows(&self) -> Vec<Vec<String>> {
@@ -80,7 +211,14 @@ impl CrudData for Posts {
                 vec![
                     x.title.clone(),
                     x.summary.clone().unwrap_or(String::new()),
-                    x.content.clone().unwrap_or(String::new()),
+                    self.tags
+                        .get(&x.id)
+                        .map(|t| t.join(", "))
+                        .unwrap_or_default(),
+                    self.authors.get(&x.user_id).cloned().unwrap_or_default(),
+                    local_time(x.created_at, &self.time_format),
+                    local_time(x.updated_at, &self.time_format),
+                    publications::status(x).to_string(),
                 ]
             })
             .collect()
@@ -89,8 +227,12 @@ impl CrudData for Posts {
     fn widths(&self) -> Vec<Constraint> {
         vec![
             Constraint::Percentage(20),
-            Constraint::Percentage(40),
-            Constraint::Percentage(40),
+            Constraint::Percentage(22),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(10),
         ]
     }
 
@@ -104,25 +246,361 @@ impl CrudData for Posts {
 
     async fn delete(&self, idx: usize) -> Result<()> {
         if let Some(cnx) = &self.db {
-            self.posts[idx].clone().delete(cnx).await?;
+            let txn = cnx.begin().await?;
+            delete_posts(&txn, &[self.posts[idx].id]).await?;
+            txn.commit().await?;
             Ok(())
         } else {
             Err(eyre!("Database is not connected"))
         }
     }
 
+    async fn describe(&self, idx: usize) -> Result<String> {
+        Ok(format!("post \"{}\"", self.posts[idx].title))
+    }
+
+    async fn describe_bulk(&self, ids: &[i32]) -> Result<String> {
+        if let Some(cnx) = &self.db {
+            let revisions = revisions::count(cnx, ids).await?;
+            Ok(format!(
+                "{}, with {}",
+                count(ids.len() as u64, "post"),
+                count(revisions, "revision")
+            ))
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
     async fn refresh(&mut self) -> Result<()> {
+        self.posts.clear();
+        self.tags.clear();
+        self.authors.clear();
+        self.first = 0;
+        self.page = 0;
+        self.load_more().await?;
+        Ok(())
+    }
+
+    async fn load_more(&mut self) -> Result<bool> {
         if let Some(cnx) = &self.db {
-            self.posts = PostEntity::find().all(cnx).await?;
+            let paginator = self.query().paginate(cnx, PAGE_SIZE);
+            if self.page == 0 {
+                self.total = paginator.num_items().await?;
+            }
+            let posts = paginator.fetch_page(self.page).await?;
+            if posts.is_empty() {
+                // Rows were deleted since the count, stop asking for more.
+                self.total = self.first * PAGE_SIZE + self.posts.len() as u64;
+                return Ok(false);
+            }
+            self.page += 1;
+            load_names(cnx, &posts, &mut self.tags, &mut self.authors).await?;
+            self.posts.extend(posts);
+            Ok(true)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_last(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let paginator = self.query().paginate(cnx, PAGE_SIZE);
+            self.total = paginator.num_items().await?;
+            self.first = self.total.saturating_sub(1) / PAGE_SIZE;
+            self.page = self.first + 1;
+            self.posts = paginator.fetch_page(self.first).await?;
+            self.tags.clear();
+            self.authors.clear();
+            load_names(cnx, &self.posts, &mut self.tags, &mut self.authors).await?;
             Ok(())
         } else {
             Err(eyre!("Database is not connected"))
         }
     }
 
+    async fn load_previous(&mut self) -> Result<usize> {
+        if self.first == 0 {
+            return Ok(0);
+        }
+        if let Some(cnx) = &self.db {
+            let posts = self
+                .query()
+                .paginate(cnx, PAGE_SIZE)
+                .fetch_page(self.first - 1)
+                .await?;
+            self.first -= 1;
+            load_names(cnx, &posts, &mut self.tags, &mut self.authors).await?;
+            let len = posts.len();
+            self.posts.splice(0..0, posts);
+            Ok(len)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_id(&mut self, id: i32) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            self.posts = PostEntity::find_by_id(id)
+                .one(cnx)
+                .await?
+                .into_iter()
+                .collect();
+            self.tags.clear();
+            self.authors.clear();
+            load_names(cnx, &self.posts, &mut self.tags, &mut self.authors).await?;
+            Ok(!self.posts.is_empty())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    fn first_row(&self) -> usize {
+        (self.first * PAGE_SIZE) as usize
+    }
+
+    fn total_rows(&self) -> usize {
+        self.total as usize
+    }
+
+    /// Tags cannot be sorted on, a post having any number of them. Statuses sort by name,
+    /// which puts drafts first, then published and scheduled posts.
+    fn set_order(&mut self, column: usize, order: Option<Order>) -> bool {
+        let column = match column {
+            0 => PostColumn::Title,
+            1 => PostColumn::Summary,
+            3 => PostColumn::UserId,
+            4 => PostColumn::CreatedAt,
+            5 => PostColumn::UpdatedAt,
+            6 => PostColumn::Status,
+            _ => return false,
+        };
+        self.order = order.map(|x| (column, x));
+        true
+    }
+
     fn to_db_id(&self, idx: usize) -> i32 {
         self.posts[idx].id
     }
+
+    fn record(&self, idx: usize) -> Result<JsonValue> {
+        Ok(serde_json::to_value(&self.posts[idx])?)
+    }
+
+    fn details(&self, idx: usize) -> Vec<(String, String)> {
+        let post = &self.posts[idx];
+        let publish_at = post
+            .publish_at
+            .map(|x| local_time(x, &self.time_format))
+            .unwrap_or_default();
+        vec![
+            ("Id".to_string(), post.id.to_string()),
+            (PostField::Title.to_string(), post.title.clone()),
+            (
+                PostField::Slug.to_string(),
+                post.slug.clone().unwrap_or_default(),
+            ),
+            (
+                PostField::Summary.to_string(),
+                post.summary.clone().unwrap_or_default(),
+            ),
+            (
+                PostField::Author.to_string(),
+                self.authors.get(&post.user_id).cloned().unwrap_or_default(),
+            ),
+            (
+                PostField::Tags.to_string(),
+                self.tags
+                    .get(&post.id)
+                    .map(|t| t.join(", "))
+                    .unwrap_or_default(),
+            ),
+            (
+                PostField::Status.to_string(),
+                publications::status(post).to_string(),
+            ),
+            ("Publish date".to_string(), publish_at),
+            (
+                "Created".to_string(),
+                local_time(post.created_at, &self.time_format),
+            ),
+            (
+                "Updated".to_string(),
+                local_time(post.updated_at, &self.time_format),
+            ),
+            (
+                PostField::Content.to_string(),
+                post.content.clone().unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn preview(&self, idx: usize) -> Option<Text<'static>> {
+        Some(markdown::render(
+            self.posts[idx].content.as_deref().unwrap_or(""),
+        ))
+    }
+
+    async fn publish(&mut self, idx: usize, publish: bool) -> Result<()> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        let post = self.posts[idx].clone();
+        let saved = if publish {
+            // A post published before keeps its date, one scheduled for later is published now.
+            let now = chrono::Utc::now().naive_utc();
+            let date = post.publish_at.filter(|x| *x <= now).unwrap_or(now);
+            publications::save(cnx, &post, Status::Published, Some(date)).await?
+        } else {
+            publications::save(cnx, &post, Status::Draft, None).await?
+        };
+        if self.drafts_only && publish {
+            // The post leaves the drafts, moving the pages after it up by one.
+            return self.refresh().await;
+        }
+        self.posts[idx] = saved;
+        Ok(())
+    }
+
+    fn set_drafts_only(&mut self, drafts_only: bool) -> bool {
+        self.drafts_only = drafts_only;
+        true
+    }
+
+    async fn bulk_choices(&self, action: &Action) -> Result<Vec<(String, Bulk)>> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        Ok(match action {
+            Action::BulkTag => TagEntity::find()
+                .order_by_asc(TagColumn::Name)
+                .all(cnx)
+                .await?
+                .into_iter()
+                .map(|x| (x.name, Bulk::Tag(x.id)))
+                .collect(),
+            Action::BulkAuthor => UserEntity::find()
+                .order_by_asc(UserColumn::Name)
+                .all(cnx)
+                .await?
+                .into_iter()
+                .map(|x| (format!("{} <{}>", x.name, x.email), Bulk::Author(x.id)))
+                .collect(),
+            // Scheduling takes a date for each post.
+            Action::BulkStatus => Status::ALL
+                .into_iter()
+                .filter(|x| *x != Status::Scheduled)
+                .map(|x| (x.to_string(), Bulk::Status(x.to_string())))
+                .collect(),
+            _ => Vec::new(),
+        })
+    }
+
+    async fn bulk(&mut self, ids: &[i32], change: &Bulk) -> Result<String> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        let txn = cnx.begin().await?;
+        let posts = PostEntity::find()
+            .filter(PostColumn::Id.is_in(ids.iter().copied()))
+            .all(&txn)
+            .await?;
+        let summary = match change {
+            Bulk::Delete => {
+                let ids: Vec<i32> = posts.iter().map(|x| x.id).collect();
+                let deleted = delete_posts(&txn, &ids).await?;
+                format!("Deleted {}", count(deleted, "post"))
+            }
+            Bulk::Tag(tag_id) => {
+                let tag = TagEntity::find_by_id(*tag_id)
+                    .one(&txn)
+                    .await?
+                    .ok_or_else(|| eyre!("Tag {} does not exist", tag_id))?;
+                let tagged: HashSet<i32> = PostTagEntity::find()
+                    .filter(PostTagColumn::TagId.eq(tag.id))
+                    .filter(PostTagColumn::PostId.is_in(posts.iter().map(|x| x.id)))
+                    .all(&txn)
+                    .await?
+                    .into_iter()
+                    .map(|x| x.post_id)
+                    .collect();
+                let mut tagged_now = 0;
+                for post in posts.iter().filter(|x| !tagged.contains(&x.id)) {
+                    let mut link: ActivePostTag = ActiveModelTrait::default();
+                    link.post_id = Set(post.id);
+                    link.tag_id = Set(tag.id);
+                    link.insert(&txn).await?;
+                    let mut post: ActivePost = post.clone().into();
+                    touch(&mut post);
+                    post.update(&txn).await?;
+                    tagged_now += 1;
+                }
+                let summary = format!("Tagged {} with \"{}\"", count(tagged_now, "post"), tag.name);
+                with_unchanged(summary, posts.len() - tagged_now as usize)
+            }
+            Bulk::Author(user_id) => {
+                let user = UserEntity::find_by_id(*user_id)
+                    .one(&txn)
+                    .await?
+                    .ok_or_else(|| eyre!("User {} does not exist", user_id))?;
+                let mut changed = 0;
+                for post in posts.iter().filter(|x| x.user_id != user.id) {
+                    // Like an edit, the version replaced is kept as a revision.
+                    revisions::snapshot(&txn, post).await?;
+                    let mut post: ActivePost = post.clone().into();
+                    post.user_id = Set(user.id);
+                    touch(&mut post);
+                    post.update(&txn).await?;
+                    changed += 1;
+                }
+                let summary = format!(
+                    "Set the author of {} to {}",
+                    count(changed, "post"),
+                    user.name
+                );
+                with_unchanged(summary, posts.len() - changed as usize)
+            }
+            Bulk::Status(status) => {
+                let status = Status::try_from(status.as_str())?;
+                if status == Status::Scheduled {
+                    return Err(eyre!("Posts are scheduled one at a time, with their date"));
+                }
+                let now = chrono::Utc::now().naive_utc();
+                let mut changed = 0;
+                for post in posts.iter().filter(|x| publications::status(x) != status) {
+                    let date = match status {
+                        Status::Published => {
+                            Some(post.publish_at.filter(|x| *x <= now).unwrap_or(now))
+                        }
+                        Status::Draft => None,
+                        _ => post.publish_at,
+                    };
+                    publications::save(&txn, post, status, date).await?;
+                    changed += 1;
+                }
+                let summary = format!("Set {} to {}", count(changed, "post"), status);
+                with_unchanged(summary, posts.len() - changed as usize)
+            }
+        };
+        txn.commit().await?;
+        Ok(summary)
+    }
+
+    fn set_config(&mut self, config: Config) {
+        self.time_format = config.time_format().to_string();
+    }
+}
+
+/// `summary`, followed by how many of the posts were left as they were, if any.
+fn with_unchanged(summary: String, unchanged: usize) -> String {
+    if unchanged == 0 {
+        summary
+    } else {
+        format!("{}, {} unchanged", summary, unchanged)
+    }
 }
 
 #[derive(Default)]
@@ -131,7 +609,200 @@ pub struct PostEdit {
     db: Option<DatabaseConnection>,
     row: Option<Post>,
     focused_field: Option<PostField>,
-    fields: HashMap<PostField, String>,
+    fields: HashMap<PostField, Input>,
+    author: Picker,
+    tags: Picker,
+    status: Picker,
+    acting_admin: String,
+    preview: bool,
+    errors: HashMap<PostField, String>,
+    /// Whether the slug is derived from the title, until it is edited by hand.
+    slug_from_title: bool,
+    /// Slug found to be used by another post when saving.
+    slug_taken: Option<String>,
+}
+
+impl PostEdit {
+    /// Build the author picker from every user, selecting `user_id` if there is one.
+    async fn author_picker(cnx: &DatabaseConnection, user_id: Option<i32>) -> Result<Picker> {
+        let items = UserEntity::find()
+            .all(cnx)
+            .await?
+            .into_iter()
+            .map(|x| PickerItem::new(x.id, format!("{} <{}>", x.name, x.email)))
+            .collect();
+        let mut picker = Picker::new(items, false);
+        if let Some(id) = user_id {
+            picker.select_ids(&[id]);
+        }
+        Ok(picker)
+    }
+
+    /// Build the tag picker from every existing tag, selecting the ones linked to `post_id`.
+    async fn tag_picker(cnx: &DatabaseConnection, post_id: Option<i32>) -> Result<Picker> {
+        let items = TagEntity::find()
+            .all(cnx)
+            .await?
+            .into_iter()
+            .map(|x| PickerItem::new(x.id, x.name))
+            .collect();
+        let mut picker = Picker::new(items, true).allow_create(true);
+        if let Some(id) = post_id {
+            let ids: Vec<i32> = PostTagEntity::find()
+                .filter(PostTagColumn::PostId.eq(id))
+                .all(cnx)
+                .await?
+                .into_iter()
+                .map(|x| x.tag_id)
+                .collect();
+            picker.select_ids(&ids);
+        }
+        Ok(picker)
+    }
+
+    /// Build the status picker, selecting `status`.
+    fn status_picker(status: Status) -> Picker {
+        let items = Status::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, x)| PickerItem::new(i as i32, x.to_string()))
+            .collect();
+        let mut picker = Picker::new(items, false);
+        if let Some(i) = Status::ALL.iter().position(|x| *x == status) {
+            picker.select_ids(&[i as i32]);
+        }
+        picker
+    }
+
+    /// Keep `slug` as used by another post, to show under the field, and fail with it.
+    fn slug_taken(&mut self, slug: String) -> color_eyre::Report {
+        self.slug_taken = Some(slug);
+        self.errors
+            .insert(PostField::Slug, "Already used by another post".to_string());
+        eyre!("Slug: Already used by another post")
+    }
+
+    /// Derive the slug from the title, unless it was edited by hand.
+    fn update_slug(&mut self) {
+        if !self.slug_from_title {
+            return;
+        }
+        let title = self.fields.get(&PostField::Title).map_or("", Input::value);
+        let slug = slugs::slugify(title);
+        let input = self.fields.entry(PostField::Slug).or_default();
+        if input.value() != slug {
+            input.set_value(&slug);
+        }
+    }
+
+    fn selected_status(&self) -> Option<Status> {
+        self.status
+            .selected()
+            .first()
+            .and_then(|x| x.id)
+            .and_then(|i| Status::ALL.get(i as usize).copied())
+    }
+
+    fn publish_date(&self) -> Option<NaiveDateTime> {
+        let value = self
+            .fields
+            .get(&PostField::PublishDate)
+            .map_or("", Input::value);
+        NaiveDateTime::parse_from_str(value.trim(), publications::DATE_FORMAT)
+            .ok()
+            .and_then(from_local)
+    }
+
+    /// Replace the tags of `post_id` with the ones selected in the picker, creating new tags
+    /// as needed.
+    async fn save_tags(&self, txn: &DatabaseTransaction, post_id: i32) -> Result<()> {
+        let mut tag_ids = Vec::new();
+        for item in self.tags.selected() {
+            let id = match item.id {
+                Some(id) => id,
+                None => {
+                    let mut tag: ActiveTag = ActiveModelTrait::default();
+                    tag.name = Set(item.label.clone());
+                    tag.insert(txn).await?.id
+                }
+            };
+            tag_ids.push(id);
+        }
+
+        PostTagEntity::delete_many()
+            .filter(PostTagColumn::PostId.eq(post_id))
+            .exec(txn)
+            .await?;
+        for tag_id in tag_ids {
+            let mut link: ActivePostTag = ActiveModelTrait::default();
+            link.post_id = Set(post_id);
+            link.tag_id = Set(tag_id);
+            link.insert(txn).await?;
+        }
+        Ok(())
+    }
+
+    fn labels(picker: &Picker) -> String {
+        let labels: Vec<&str> = picker.selected().iter().map(|x| x.label.as_str()).collect();
+        labels.join(", ")
+    }
+
+    fn focused_picker(&mut self) -> Option<&mut Picker> {
+        match self.focused_field {
+            Some(PostField::Author) => Some(&mut self.author),
+            Some(PostField::Tags) => Some(&mut self.tags),
+            Some(PostField::Status) => Some(&mut self.status),
+            _ => None,
+        }
+    }
+
+    /// Why the value of `field` cannot be saved, if it cannot.
+    fn error(&self, field: PostField) -> Option<String> {
+        let value = self.fields.get(&field).map_or("", Input::value);
+        match field {
+            PostField::Title => required(value).or_else(|| max_length(value, 255)),
+            PostField::Slug if self.slug_taken.as_deref() == Some(value) => {
+                Some("Already used by another post".to_string())
+            }
+            PostField::Slug => required(value)
+                .or_else(|| max_length(value, 255))
+                .or_else(|| {
+                    (!slugs::is_valid(value))
+                        .then(|| "Only lowercase letters and digits between hyphens".to_string())
+                }),
+            PostField::Author if self.author.selected().is_empty() => {
+                Some("Select an author".to_string())
+            }
+            PostField::Status if self.status.selected().is_empty() => {
+                Some("Select a status".to_string())
+            }
+            PostField::PublishDate if value.trim().is_empty() => {
+                let scheduled = self.selected_status() == Some(Status::Scheduled);
+                scheduled.then(|| "Required to schedule the post".to_string())
+            }
+            PostField::PublishDate => datetime(value, publications::DATE_FORMAT).or_else(|| {
+                self.publish_date()
+                    .is_none()
+                    .then(|| "Skipped by the change to summer time".to_string())
+            }),
+            _ => None,
+        }
+    }
+
+    fn field_constraint(&self, field: PostField) -> Constraint {
+        match field {
+            PostField::Title => Constraint::Length(3),
+            PostField::Slug => Constraint::Length(3),
+            PostField::Summary => Constraint::Percentage(20),
+            PostField::Author if self.focused_field == Some(field) => Constraint::Percentage(25),
+            PostField::Author => Constraint::Length(3),
+            PostField::Tags => Constraint::Percentage(20),
+            PostField::Status if self.focused_field == Some(field) => Constraint::Length(7),
+            PostField::Status => Constraint::Length(3),
+            PostField::PublishDate => Constraint::Length(3),
+            PostField::Content => Constraint::Min(3),
+        }
+    }
 }
 
 #[async_trait]
@@ -140,17 +811,39 @@ impl CrudRow for PostEdit {
         if let Some(cnx) = &self.db {
             self.row = PostEntity::find_by_id(idx).one(cnx).await?;
             if let Some(post) = &self.row {
-                self.fields.insert(PostField::Title, post.title.clone());
+                self.fields
+                    .insert(PostField::Title, Input::new(&post.title));
                 self.fields.insert(
                     PostField::Summary,
-                    post.summary.clone().unwrap_or("".to_string()),
+                    Input::new(post.summary.as_deref().unwrap_or("")).multiline(true),
                 );
                 self.fields.insert(
                     PostField::Content,
-                    post.content.clone().unwrap_or("".to_string()),
+                    Input::new(post.content.as_deref().unwrap_or("")).multiline(true),
                 );
+                self.author = Self::author_picker(cnx, Some(post.user_id)).await?;
+                self.tags = Self::tag_picker(cnx, Some(post.id)).await?;
+                self.status = Self::status_picker(publications::status(post));
+                let date = post
+                    .publish_at
+                    .map(|x| to_local(x).format(publications::DATE_FORMAT).to_string())
+                    .unwrap_or_default();
+                self.fields
+                    .insert(PostField::PublishDate, Input::new(&date));
+                // Posts saved before there were slugs get one from their title.
+                let slug = post.slug.clone();
+                self.slug_from_title = slug
+                    .as_ref()
+                    .is_none_or(|x| *x == slugs::slugify(&post.title));
+                self.fields.insert(
+                    PostField::Slug,
+                    Input::new(slug.as_deref().unwrap_or_default()),
+                );
+                self.update_slug();
                 self.mode = CrudEditMode::Edit;
                 self.focused_field = Some(PostField::Title);
+                self.errors.clear();
+                self.slug_taken = None;
                 return Ok(());
             }
 
@@ -160,17 +853,49 @@ impl CrudRow for PostEdit {
         }
     }
 
-    fn new(&mut self) {
-        self.fields.insert(PostField::Title, String::new());
-        self.fields.insert(PostField::Summary, String::new());
-        self.fields.insert(PostField::Content, String::new());
+    async fn new(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let admin = if self.acting_admin.is_empty() {
+                None
+            } else {
+                User::find_by_email(cnx, &self.acting_admin)
+                    .await
+                    .ok()
+                    .map(|x| x.id)
+            };
+            self.author = Self::author_picker(cnx, admin).await?;
+            self.tags = Self::tag_picker(cnx, None).await?;
+        }
+        self.row = None;
+        self.status = Self::status_picker(Status::Draft);
+        self.fields.insert(PostField::PublishDate, Input::default());
+        self.fields.insert(PostField::Title, Input::default());
+        self.fields.insert(PostField::Slug, Input::default());
+        self.slug_from_title = true;
+        self.slug_taken = None;
+        self.fields
+            .insert(PostField::Summary, Input::default().multiline(true));
+        self.fields
+            .insert(PostField::Content, Input::default().multiline(true));
         self.focused_field = Some(PostField::Title);
         self.mode = CrudEditMode::New;
+        self.errors.clear();
+        Ok(())
     }
 
     async fn save(&mut self) -> Result<()> {
-        if let Some(cnx) = &self.db {
-            let user = User::find_by_email(cnx, "a.reversat@gmail.com").await?;
+        if let Some(cnx) = self.db.clone() {
+            let author = self
+                .author
+                .selected()
+                .first()
+                .and_then(|x| x.id)
+                .ok_or_else(|| eyre!("No author selected"))?;
+            let slug = self
+                .fields
+                .get(&PostField::Slug)
+                .map_or("", Input::value)
+                .to_owned();
             let mut post: ActivePost = match self.mode {
                 CrudEditMode::New => ActiveModelTrait::default(),
                 CrudEditMode::Edit => {
@@ -184,76 +909,250 @@ impl CrudRow for PostEdit {
             post.title = Set(self
                 .fields
                 .get(&PostField::Title)
-                .unwrap_or(&"".to_string())
+                .map_or("", Input::value)
                 .to_owned());
             post.summary = Set(Some(
                 self.fields
                     .get(&PostField::Summary)
-                    .unwrap_or(&"".to_string())
+                    .map_or("", Input::value)
                     .to_owned(),
             ));
             post.content = Set(Some(
                 self.fields
                     .get(&PostField::Content)
-                    .unwrap_or(&"".to_string())
+                    .map_or("", Input::value)
                     .to_owned(),
             ));
-            post.user_id = Set(user.id);
+            if self.row.as_ref().map(|x| x.user_id) != Some(author) {
+                post.user_id = Set(author);
+            }
+            let status = self
+                .selected_status()
+                .ok_or_else(|| eyre!("No status selected"))?;
+            let date = match (status, self.publish_date()) {
+                (Status::Published, None) => Some(chrono::Utc::now().naive_utc()),
+                (_, date) => date,
+            };
+            publications::set(&mut post, status, date);
+            post.slug = Set(Some(slug.clone()));
+            touch(&mut post);
+            if let CrudEditMode::New = self.mode {
+                post.created_at = post.updated_at.clone();
+            }
 
-            match self.mode {
-                CrudEditMode::Edit => post.update(cnx).await?,
-                CrudEditMode::New => post.insert(cnx).await?,
+            let txn = cnx.begin().await?;
+            if slugs::is_taken(&txn, &slug, self.id()).await? {
+                return Err(self.slug_taken(slug));
+            }
+            if let (CrudEditMode::Edit, Some(previous)) = (&self.mode, &self.row) {
+                revisions::snapshot(&txn, previous).await?;
+            }
+            let saved = match self.mode {
+                CrudEditMode::Edit => post.update(&txn).await,
+                CrudEditMode::New => post.insert(&txn).await,
+            };
+            let post = match saved {
+                Ok(post) => post,
+                // Another post was given the slug since the check.
+                Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                    return Err(self.slug_taken(slug));
+                }
+                Err(e) => return Err(e.into()),
             };
+            self.save_tags(&txn, post.id).await?;
+            txn.commit().await?;
+            self.row = Some(post);
+            self.mode = CrudEditMode::Edit;
         }
         Ok(())
     }
 
+    fn validate(&mut self) -> Result<()> {
+        let errors: Vec<(PostField, String)> = (0..8)
+            .map(|x| PostField::try_from(x).unwrap())
+            .filter_map(|x| self.error(x).map(|e| (x, e)))
+            .collect();
+        let summary: Vec<(String, String)> = errors
+            .iter()
+            .map(|(field, e)| (field.to_string(), e.clone()))
+            .collect();
+        self.errors = errors.into_iter().collect();
+        validation::check(&summary)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.row.as_ref().map(|x| x.id)
+    }
+
+    fn values(&self) -> Vec<String> {
+        (0..8)
+            .map(|x| match PostField::try_from(x).unwrap() {
+                PostField::Author => Self::labels(&self.author),
+                PostField::Tags => Self::labels(&self.tags),
+                PostField::Status => Self::labels(&self.status),
+                field => self.fields.get(&field).map_or("", Input::value).to_string(),
+            })
+            .collect()
+    }
+
+    fn set_field(&mut self, name: &str, value: &str) -> Result<()> {
+        let field = (0..8)
+            .map(|x| PostField::try_from(x).unwrap())
+            .find(|x| x.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| eyre!("Unknown field \"{}\"", name))?;
+        match field {
+            PostField::Author => {
+                let email = format!("<{}>", value);
+                let id = self
+                    .author
+                    .items()
+                    .iter()
+                    .find(|x| x.label == value || x.label.ends_with(&email))
+                    .and_then(|x| x.id)
+                    .ok_or_else(|| eyre!("Unknown author \"{}\"", value))?;
+                self.author.select_ids(&[id]);
+            }
+            PostField::Tags => {
+                let labels: Vec<&str> = value.split(',').filter(|x| !x.trim().is_empty()).collect();
+                self.tags.select_labels(&labels)?;
+            }
+            PostField::Status => self.status.select_labels(&[value])?,
+            PostField::Slug => {
+                self.fields.insert(field, Input::new(value));
+                self.slug_from_title = false;
+            }
+            _ => {
+                self.fields.insert(
+                    field,
+                    Input::new(value)
+                        .multiline(field == PostField::Summary || field == PostField::Content),
+                );
+            }
+        }
+        self.update_slug();
+        Ok(())
+    }
+
     fn focus_next_field(&mut self) {
         if let Some(field) = self.focused_field {
             self.focused_field = Some(field.next());
         }
     }
 
-    fn input(&mut self, c: char) {
-        if let Some(fieldname) = self.focused_field {
-            if let Some(field) = self.fields.get_mut(&fieldname) {
-                field.push(c);
+    fn edited(&mut self) {
+        match self.focused_field {
+            Some(PostField::Title) => self.update_slug(),
+            // Emptying the slug, or typing the one of the title, has it follow the title again.
+            Some(PostField::Slug) => {
+                let title = self.fields.get(&PostField::Title).map_or("", Input::value);
+                let slug = self.fields.get(&PostField::Slug).map_or("", Input::value);
+                self.slug_from_title = slug.is_empty() || slug == slugs::slugify(title);
             }
+            _ => (),
+        }
+    }
+
+    fn toggle_preview(&mut self) {
+        self.preview = !self.preview;
+    }
+
+    fn up(&mut self) {
+        if let Some(picker) = self.focused_picker() {
+            picker.up();
+        }
+    }
+
+    fn down(&mut self) {
+        if let Some(picker) = self.focused_picker() {
+            picker.down();
+        }
+    }
+
+    fn focused_input(&mut self) -> Option<&mut Input> {
+        self.focused_field
+            .and_then(|field| self.fields.get_mut(&field))
+    }
+
+    fn input(&mut self, c: char) {
+        if let Some(picker) = self.focused_picker() {
+            picker.input(c);
         }
     }
 
     fn delete_last_char(&mut self) {
-        if let Some(fieldname) = self.focused_field {
-            if let Some(field) = self.fields.get_mut(&fieldname) {
-                field.pop();
-            }
+        if let Some(picker) = self.focused_picker() {
+            picker.delete_last_char();
         }
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
         let layout = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(10),
-                Constraint::Percentage(30),
-                Constraint::Percentage(60),
-            ])
+            .constraints(
+                (0..8)
+                    .map(|x| self.field_constraint(PostField::try_from(x).unwrap()))
+                    .collect::<Vec<Constraint>>(),
+            )
             .split(area);
 
-        for i in 0..3 {
+        for i in 0..8 {
             let field = PostField::try_from(i).unwrap();
             let style = if Some(field) == self.focused_field {
                 FormStyle::highlighted()
             } else {
                 FormStyle::normal()
             };
+            let focused = Some(field) == self.focused_field;
+            let mut title = field.to_string();
+            if let Some(input) = self
+                .fields
+                .get(&field)
+                .filter(|x| focused && x.is_multiline())
+            {
+                let (line, column) = input.cursor_position();
+                title = format!("{} (Ln {}, Col {})", title, line + 1, column + 1);
+            }
+            if field == PostField::PublishDate {
+                title.push_str(" (local time)");
+            }
             let block = Block::default()
                 .borders(Borders::ALL)
-                .title(field.to_string())
+                .title(title)
                 .style(style);
-            let value = Paragraph::new(self.fields.get(&field).unwrap_or(&"".to_string()).clone())
-                .block(block);
-            f.render_widget(value, layout[i]);
+            let block = validation::with_error(block, self.errors.get(&field));
+            match field {
+                PostField::Author => {
+                    self.author.draw(f, layout[i], block, focused);
+                    continue;
+                }
+                PostField::Tags => {
+                    self.tags.draw(f, layout[i], block, focused);
+                    continue;
+                }
+                PostField::Status => {
+                    self.status.draw(f, layout[i], block, focused);
+                    continue;
+                }
+                _ => (),
+            }
+            let mut area = layout[i];
+            if field == PostField::Content && self.preview {
+                let halves = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                area = halves[0];
+                let content = self.fields.get(&field).map_or("", Input::value);
+                f.render_widget(
+                    Paragraph::new(markdown::render(content))
+                        .wrap(Wrap { trim: false })
+                        .block(Block::default().borders(Borders::ALL).title("Preview")),
+                    halves[1],
+                );
+            }
+            if let Some(input) = self.fields.get_mut(&field) {
+                input.draw(f, area, block, focused);
+            }
         }
         Ok(())
     }
@@ -261,4 +1160,8 @@ impl CrudRow for PostEdit {
     fn set_db(&mut self, db: Option<DatabaseConnection>) {
         self.db = db;
     }
+
+    fn set_config(&mut self, config: Config) {
+        self.acting_admin = config.acting_admin;
+    }
 }
@@ -0,0 +1,100 @@
+//! Publication status of posts. A post is a draft until it is published, or scheduled to be
+//! on its publish date, and archived once it is taken down.
+
+use std::fmt::Display;
+
+use blogapi::models::_entities::posts::{
+    ActiveModel as ActivePost, Column as PostColumn, Entity as PostEntity, Model as Post,
+};
+use chrono::NaiveDateTime;
+use color_eyre::{eyre::eyre, Result};
+use sea_orm::{
+    sea_query::SimpleExpr, ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait,
+    QueryFilter, QueryOrder, Select, Set,
+};
+
+use super::posts;
+
+/// Format of publish dates, as shown and typed.
+pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Status {
+    #[default]
+    Draft,
+    Scheduled,
+    Published,
+    Archived,
+}
+
+impl Status {
+    pub const ALL: [Status; 4] = [
+        Status::Draft,
+        Status::Scheduled,
+        Status::Published,
+        Status::Archived,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Status::Draft => "draft",
+            Status::Scheduled => "scheduled",
+            Status::Published => "published",
+            Status::Archived => "archived",
+        }
+    }
+}
+
+impl TryFrom<&str> for Status {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Status::ALL
+            .into_iter()
+            .find(|x| x.key().eq_ignore_ascii_case(value))
+            .ok_or_else(|| eyre!("Unknown status \"{}\"", value))
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Status of `post`. Unknown statuses count as drafts, so as not to publish by mistake.
+pub fn status(post: &Post) -> Status {
+    Status::try_from(post.status.as_str()).unwrap_or_default()
+}
+
+/// Condition on posts to have `status`.
+pub fn has_status(status: Status) -> SimpleExpr {
+    PostColumn::Status.eq(status.key())
+}
+
+/// Set the publication of `post`, to be saved with the rest of it.
+pub fn set(post: &mut ActivePost, status: Status, publish_at: Option<NaiveDateTime>) {
+    post.status = Set(status.key().to_string());
+    post.publish_at = Set(publish_at);
+}
+
+/// Save the publication of `post`.
+pub async fn save<C: ConnectionTrait>(
+    cnx: &C,
+    post: &Post,
+    status: Status,
+    publish_at: Option<NaiveDateTime>,
+) -> Result<Post> {
+    let mut post: ActivePost = post.clone().into();
+    set(&mut post, status, publish_at);
+    posts::touch(&mut post);
+    Ok(post.update(cnx).await?)
+}
+
+/// The scheduled posts, the next one to go live first.
+pub fn scheduled() -> Select<PostEntity> {
+    PostEntity::find()
+        .filter(has_status(Status::Scheduled))
+        .order_by_asc(PostColumn::PublishAt)
+        .order_by_asc(PostColumn::Id)
+}
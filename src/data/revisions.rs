@@ -0,0 +1,221 @@
+//! Earlier versions of posts. Saving a post keeps the version it replaces as a revision, which
+//! can be compared with the current version and restored.
+
+use std::collections::HashMap;
+
+use blogapi::models::_entities::post_revisions::{
+    ActiveModel as ActiveRevision, Column as RevisionColumn, Entity as RevisionEntity,
+};
+use blogapi::models::_entities::posts::{
+    ActiveModel as ActivePost, Entity as PostEntity, Model as Post,
+};
+use blogapi::models::_entities::users::{Column as UserColumn, Entity as UserEntity};
+use color_eyre::{eyre::eyre, Result};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use super::posts;
+
+/// A revision, whose `created_at` is when it was taken, that is when this version was
+/// replaced, and `updated_at` when this version was saved.
+pub use blogapi::models::_entities::post_revisions::Model as Revision;
+
+/// Keep `post` as it is before it is overwritten.
+pub async fn snapshot<C: ConnectionTrait>(cnx: &C, post: &Post) -> Result<()> {
+    let revision = ActiveRevision {
+        post_id: Set(post.id),
+        created_at: Set(chrono::Utc::now().naive_utc()),
+        updated_at: Set(post.updated_at),
+        title: Set(post.title.clone()),
+        summary: Set(post.summary.clone()),
+        content: Set(post.content.clone()),
+        user_id: Set(post.user_id),
+        ..Default::default()
+    };
+    revision.insert(cnx).await?;
+    Ok(())
+}
+
+/// Delete the revisions of the posts `post_ids`, before the posts themselves.
+pub async fn delete<C: ConnectionTrait>(cnx: &C, post_ids: &[i32]) -> Result<()> {
+    RevisionEntity::delete_many()
+        .filter(RevisionColumn::PostId.is_in(post_ids.iter().copied()))
+        .exec(cnx)
+        .await?;
+    Ok(())
+}
+
+/// Number of revisions of the posts `post_ids`.
+pub async fn count<C: ConnectionTrait>(cnx: &C, post_ids: &[i32]) -> Result<u64> {
+    Ok(RevisionEntity::find()
+        .filter(RevisionColumn::PostId.is_in(post_ids.iter().copied()))
+        .count(cnx)
+        .await?)
+}
+
+/// Revisions of the post `post_id`, latest first, with the name of their author.
+pub async fn list(cnx: &DatabaseConnection, post_id: i32) -> Result<Vec<(Revision, String)>> {
+    let revisions = RevisionEntity::find()
+        .filter(RevisionColumn::PostId.eq(post_id))
+        .order_by_desc(RevisionColumn::CreatedAt)
+        .order_by_desc(RevisionColumn::Id)
+        .all(cnx)
+        .await?;
+    let authors: HashMap<i32, String> = UserEntity::find()
+        .filter(UserColumn::Id.is_in(revisions.iter().map(|x| x.user_id)))
+        .all(cnx)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x.name))
+        .collect();
+    Ok(revisions
+        .into_iter()
+        .map(|x| {
+            let author = authors.get(&x.user_id).cloned().unwrap_or_default();
+            (x, author)
+        })
+        .collect())
+}
+
+/// Put the post of revision `id` back as it was then. The version it replaces is kept as a
+/// revision too, so that restoring can be undone.
+pub async fn restore(cnx: &DatabaseConnection, id: i32) -> Result<Post> {
+    let txn = cnx.begin().await?;
+    let revision = RevisionEntity::find_by_id(id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| eyre!("Revision {} does not exist", id))?;
+    let post = PostEntity::find_by_id(revision.post_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| eyre!("Post {} does not exist", revision.post_id))?;
+    snapshot(&txn, &post).await?;
+    let mut post: ActivePost = post.into();
+    post.title = Set(revision.title);
+    post.summary = Set(revision.summary);
+    post.content = Set(revision.content);
+    post.user_id = Set(revision.user_id);
+    posts::touch(&mut post);
+    let post = post.update(&txn).await?;
+    txn.commit().await?;
+    Ok(post)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Diff<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Lines of `old` and `new`, those of the longest sequence they have in common being the same
+/// and the others removed from `old` or added in `new`.
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Diff<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // The lines both start and end with are left out of the search.
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut lines: Vec<Diff> = old[..prefix].iter().map(|x| Diff::Same(x)).collect();
+    diff_lines(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+        &mut lines,
+    );
+    lines.extend(old[old.len() - suffix..].iter().map(|x| Diff::Same(x)));
+    lines
+}
+
+/// Append the diff of `old` and `new` to `lines`, splitting `old` in halves where a longest
+/// common sequence goes through, so as to need memory linear in their length (Hirschberg).
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str], lines: &mut Vec<Diff<'a>>) {
+    if old.is_empty() {
+        lines.extend(new.iter().map(|x| Diff::Added(x)));
+        return;
+    }
+    if new.is_empty() {
+        lines.extend(old.iter().map(|x| Diff::Removed(x)));
+        return;
+    }
+    if old.len() == 1 {
+        if let Some(j) = new.iter().position(|x| *x == old[0]) {
+            lines.extend(new[..j].iter().map(|x| Diff::Added(x)));
+            lines.push(Diff::Same(old[0]));
+            lines.extend(new[j + 1..].iter().map(|x| Diff::Added(x)));
+        } else {
+            lines.push(Diff::Removed(old[0]));
+            lines.extend(new.iter().map(|x| Diff::Added(x)));
+        }
+        return;
+    }
+
+    let mid = old.len() / 2;
+    let head = common_lengths(&old[..mid], new);
+    let reversed = |lines: &[&'a str]| lines.iter().rev().copied().collect::<Vec<_>>();
+    let tail = common_lengths(&reversed(&old[mid..]), &reversed(new));
+    let split = (0..=new.len())
+        .max_by_key(|j| (head[*j] + tail[new.len() - j], std::cmp::Reverse(*j)))
+        .unwrap_or(0);
+    diff_lines(&old[..mid], &new[..split], lines);
+    diff_lines(&old[mid..], &new[split..], lines);
+}
+
+/// Length of the longest common sequence of `old` and of every prefix of `new`, keeping a
+/// single row of the table at a time.
+fn common_lengths(old: &[&str], new: &[&str]) -> Vec<usize> {
+    let mut row = vec![0; new.len() + 1];
+    for a in old {
+        let mut diagonal = 0;
+        for (j, b) in new.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == b {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff("one\ntwo\nthree", "one\n2\nthree\nfour"),
+            vec![
+                Diff::Same("one"),
+                Diff::Removed("two"),
+                Diff::Added("2"),
+                Diff::Same("three"),
+                Diff::Added("four"),
+            ]
+        );
+        assert_eq!(diff("", "a"), vec![Diff::Added("a")]);
+        assert_eq!(
+            diff("a\nb\nc\nd", "b\nx\nd\ne"),
+            vec![
+                Diff::Removed("a"),
+                Diff::Same("b"),
+                Diff::Removed("c"),
+                Diff::Added("x"),
+                Diff::Same("d"),
+                Diff::Added("e"),
+            ]
+        );
+    }
+}
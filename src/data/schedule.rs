@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use blogapi::models::_entities::posts::{
+    Column as PostColumn, Entity as PostEntity, Model as Post,
+};
+use blogapi::models::_entities::users::{Column as UserColumn, Entity as UserEntity};
+use chrono::NaiveDateTime;
+use color_eyre::{eyre::eyre, Result};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    style::{FormStyle, ScheduleStyle},
+    widgets::{datetime::DateTimeInput, input::Input},
+};
+
+use super::{
+    from_local,
+    publications::{self, Status},
+    to_local, validation, CrudData, CrudRow, PAGE_SIZE,
+};
+
+/// A scheduled post with its author.
+struct Scheduled {
+    post: Post,
+    author: String,
+}
+
+impl Scheduled {
+    fn publish_at(&self) -> NaiveDateTime {
+        self.post.publish_at.unwrap_or_default()
+    }
+
+    /// Whether the post should be live already.
+    fn overdue(&self) -> bool {
+        self.publish_at() <= chrono::Utc::now().naive_utc()
+    }
+}
+
+/// `posts` with the names of their authors.
+async fn with_authors(cnx: &DatabaseConnection, posts: Vec<Post>) -> Result<Vec<Scheduled>> {
+    let authors: HashMap<i32, String> = UserEntity::find()
+        .filter(UserColumn::Id.is_in(posts.iter().map(|x| x.user_id)))
+        .all(cnx)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x.name))
+        .collect();
+    Ok(posts
+        .into_iter()
+        .map(|post| {
+            let author = authors.get(&post.user_id).cloned().unwrap_or_default();
+            Scheduled { post, author }
+        })
+        .collect())
+}
+
+/// The scheduled posts, in the order they go live.
+#[derive(Default)]
+pub struct Schedule {
+    db: Option<DatabaseConnection>,
+    posts: Vec<Scheduled>,
+    /// First loaded page, past 0 after jumping to the end.
+    first: u64,
+    page: u64,
+    total: u64,
+}
+
+#[async_trait]
+impl CrudData for Schedule {
+    fn headers(&self) -> Vec<String> {
+        vec![
+            "Publish date".to_string(),
+            "Title".to_string(),
+            "Author".to_string(),
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.posts
+            .iter()
+            .map(|x| {
+                let mut date = to_local(x.publish_at())
+                    .format(publications::DATE_FORMAT)
+                    .to_string();
+                if x.overdue() {
+                    date.push_str(" (overdue)");
+                }
+                vec![date, x.post.title.clone(), x.author.clone()]
+            })
+            .collect()
+    }
+
+    fn widths(&self) -> Vec<Constraint> {
+        vec![
+            Constraint::Length(26),
+            Constraint::Percentage(60),
+            Constraint::Percentage(40),
+        ]
+    }
+
+    fn num_rows(&self) -> usize {
+        self.posts.len()
+    }
+
+    fn set_db(&mut self, cnx: Option<DatabaseConnection>) {
+        self.db = cnx;
+    }
+
+    async fn delete(&self, idx: usize) -> Result<()> {
+        Err(eyre!("Posts are deleted from the Posts tab"))
+    }
+
+    async fn describe(&self, idx: usize) -> Result<String> {
+        Ok(format!("post \"{}\"", self.posts[idx].post.title))
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        self.posts.clear();
+        self.first = 0;
+        self.page = 0;
+        self.load_more().await?;
+        Ok(())
+    }
+
+    async fn load_more(&mut self) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            let paginator = publications::scheduled().paginate(cnx, PAGE_SIZE);
+            if self.page == 0 {
+                self.total = paginator.num_items().await?;
+            }
+            let page = paginator.fetch_page(self.page).await?;
+            if page.is_empty() {
+                // Rows were deleted since the count, stop asking for more.
+                self.total = self.first * PAGE_SIZE + self.posts.len() as u64;
+                return Ok(false);
+            }
+            self.page += 1;
+            self.posts.extend(with_authors(cnx, page).await?);
+            Ok(true)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_last(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let paginator = publications::scheduled().paginate(cnx, PAGE_SIZE);
+            self.total = paginator.num_items().await?;
+            self.first = self.total.saturating_sub(1) / PAGE_SIZE;
+            self.page = self.first + 1;
+            let page = paginator.fetch_page(self.first).await?;
+            self.posts = with_authors(cnx, page).await?;
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_previous(&mut self) -> Result<usize> {
+        if self.first == 0 {
+            return Ok(0);
+        }
+        if let Some(cnx) = &self.db {
+            let page = publications::scheduled()
+                .paginate(cnx, PAGE_SIZE)
+                .fetch_page(self.first - 1)
+                .await?;
+            self.first -= 1;
+            let posts = with_authors(cnx, page).await?;
+            let len = posts.len();
+            self.posts.splice(0..0, posts);
+            Ok(len)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_id(&mut self, id: i32) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            let post = publications::scheduled()
+                .filter(PostColumn::Id.eq(id))
+                .one(cnx)
+                .await?;
+            self.posts = with_authors(cnx, post.into_iter().collect()).await?;
+            Ok(!self.posts.is_empty())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    fn first_row(&self) -> usize {
+        (self.first * PAGE_SIZE) as usize
+    }
+
+    fn total_rows(&self) -> usize {
+        self.total as usize
+    }
+
+    fn set_order(&mut self, column: usize, order: Option<Order>) -> bool {
+        false
+    }
+
+    fn to_db_id(&self, idx: usize) -> i32 {
+        self.posts[idx].post.id
+    }
+
+    fn record(&self, idx: usize) -> Result<JsonValue> {
+        Ok(serde_json::to_value(&self.posts[idx].post)?)
+    }
+
+    fn details(&self, idx: usize) -> Vec<(String, String)> {
+        let scheduled = &self.posts[idx];
+        let post = &scheduled.post;
+        vec![
+            ("Id".to_string(), post.id.to_string()),
+            ("Title".to_string(), post.title.clone()),
+            ("Slug".to_string(), post.slug.clone().unwrap_or_default()),
+            ("Author".to_string(), scheduled.author.clone()),
+            (
+                "Publish date".to_string(),
+                to_local(scheduled.publish_at())
+                    .format(publications::DATE_FORMAT)
+                    .to_string(),
+            ),
+            ("Status".to_string(), publications::status(post).to_string()),
+            (
+                "Summary".to_string(),
+                post.summary.clone().unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn row_style(&self, idx: usize) -> Style {
+        if self.posts[idx].overdue() {
+            ScheduleStyle::overdue()
+        } else {
+            Style::default()
+        }
+    }
+
+    /// Publish the post now, or take it back to a draft, after which it leaves the schedule.
+    /// The rows are loaded again, as the pages after it have moved up by one.
+    async fn publish(&mut self, idx: usize, publish: bool) -> Result<()> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        let post = &self.posts[idx].post;
+        if publish {
+            let now = chrono::Utc::now().naive_utc();
+            publications::save(cnx, post, Status::Published, Some(now)).await?;
+        } else {
+            publications::save(cnx, post, Status::Draft, None).await?;
+        }
+        self.refresh().await
+    }
+}
+
+/// Rescheduling of a post.
+#[derive(Default)]
+pub struct ScheduleEdit {
+    db: Option<DatabaseConnection>,
+    post: Option<Post>,
+    publish_at: Option<DateTimeInput>,
+    error: Option<String>,
+}
+
+impl ScheduleEdit {
+    /// The publish date typed, in UTC.
+    fn publish_at(&self) -> Option<NaiveDateTime> {
+        self.publish_at.as_ref().and_then(|x| from_local(x.value()))
+    }
+
+    fn error(&self) -> Option<String> {
+        self.publish_at.as_ref()?;
+        match self.publish_at() {
+            None => Some("Skipped by the change to summer time".to_string()),
+            Some(date) if date <= chrono::Utc::now().naive_utc() => {
+                Some("Must be in the future".to_string())
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CrudRow for ScheduleEdit {
+    async fn edit(&mut self, idx: i32) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            self.post = PostEntity::find_by_id(idx).one(cnx).await?;
+            let date = self
+                .post
+                .as_ref()
+                .and_then(|x| x.publish_at)
+                .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+            self.publish_at = Some(DateTimeInput::new(to_local(date)));
+            self.error = None;
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn new(&mut self) -> Result<()> {
+        Err(eyre!("Posts are scheduled from the Posts tab"))
+    }
+
+    async fn save(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let (Some(post), Some(publish_at)) = (&self.post, self.publish_at()) else {
+                return Err(eyre!("Edit mode with no row"));
+            };
+            let post = publications::save(cnx, post, Status::Scheduled, Some(publish_at)).await?;
+            self.post = Some(post);
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    fn validate(&mut self) -> Result<()> {
+        self.error = self.error();
+        let errors: Vec<(String, String)> = self
+            .error
+            .iter()
+            .map(|x| ("Publish date".to_string(), x.clone()))
+            .collect();
+        validation::check(&errors)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.post.as_ref().map(|x| x.id)
+    }
+
+    fn values(&self) -> Vec<String> {
+        self.publish_at
+            .iter()
+            .map(|x| x.value().format(publications::DATE_FORMAT).to_string())
+            .collect()
+    }
+
+    fn set_field(&mut self, name: &str, value: &str) -> Result<()> {
+        if !name.eq_ignore_ascii_case("publishdate") {
+            return Err(eyre!("Unknown field \"{}\"", name));
+        }
+        let date = NaiveDateTime::parse_from_str(value.trim(), publications::DATE_FORMAT)?;
+        self.publish_at = Some(DateTimeInput::new(date));
+        Ok(())
+    }
+
+    fn focus_next_field(&mut self) {}
+
+    fn focused_input(&mut self) -> Option<&mut Input> {
+        None
+    }
+
+    fn up(&mut self) {
+        if let Some(input) = &mut self.publish_at {
+            input.up();
+        }
+    }
+
+    fn down(&mut self) {
+        if let Some(input) = &mut self.publish_at {
+            input.down();
+        }
+    }
+
+    fn left(&mut self) {
+        if let Some(input) = &mut self.publish_at {
+            input.left();
+        }
+    }
+
+    fn right(&mut self) {
+        if let Some(input) = &mut self.publish_at {
+            input.right();
+        }
+    }
+
+    fn input(&mut self, c: char) {
+        if let Some(input) = &mut self.publish_at {
+            input.input(c);
+        }
+    }
+
+    fn delete_last_char(&mut self) {
+        if let Some(input) = &mut self.publish_at {
+            input.backspace();
+        }
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .split(area);
+        let title = self.post.as_ref().map_or("", |x| x.title.as_str());
+        f.render_widget(
+            Paragraph::new(title).block(Block::default().borders(Borders::ALL).title("Title")),
+            layout[0],
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Publish date (local time)")
+            .style(FormStyle::highlighted());
+        let block = validation::with_error(block, self.error.as_ref());
+        if let Some(input) = &self.publish_at {
+            input.draw(f, layout[1], block, true);
+        }
+        Ok(())
+    }
+
+    fn set_db(&mut self, db: Option<DatabaseConnection>) {
+        self.db = db;
+    }
+}
@@ -0,0 +1,52 @@
+//! Slugs of posts, the part of their URL that names them.
+
+use blogapi::models::_entities::posts::{Column as PostColumn, Entity as PostEntity};
+use color_eyre::Result;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+
+/// `text` in lowercase ASCII, with every run of other chars turned into a single hyphen.
+pub fn slugify(text: &str) -> String {
+    let ascii = deunicode::deunicode(text).to_lowercase();
+    let words: Vec<&str> = ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect();
+    words.join("-")
+}
+
+/// Whether `slug` is made of lowercase letters and digits separated by single hyphens.
+pub fn is_valid(slug: &str) -> bool {
+    slugify(slug) == slug
+}
+
+/// Whether a post other than `post_id` has `slug`.
+pub async fn is_taken<C: ConnectionTrait>(
+    cnx: &C,
+    slug: &str,
+    post_id: Option<i32>,
+) -> Result<bool> {
+    let mut query = PostEntity::find().filter(PostColumn::Slug.eq(slug));
+    if let Some(id) = post_id {
+        query = query.filter(PostColumn::Id.ne(id));
+    }
+    Ok(query.one(cnx).await?.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(
+            slugify("  Crème brûlée -- à la carte "),
+            "creme-brulee-a-la-carte"
+        );
+        assert_eq!(slugify("Straße 2024"), "strasse-2024");
+        assert!(is_valid("a-b-1"));
+        assert!(!is_valid("a--b"));
+    }
+}
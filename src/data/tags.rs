@@ -0,0 +1,394 @@
+use std::{collections::HashMap, fmt::Display};
+
+use async_trait::async_trait;
+use blogapi::models::_entities::posts_tags::{Column as PostTagColumn, Entity as PostTagEntity};
+use blogapi::models::_entities::tags::{
+    ActiveModel as ActiveTag, Column as TagColumn, Entity as TagEntity, Model as Tag,
+};
+use color_eyre::{eyre::eyre, Result};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders},
+    Frame,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, Set, TransactionTrait,
+};
+use serde_json::Value as JsonValue;
+
+use crate::{action::Bulk, style::FormStyle, widgets::input::Input};
+
+use super::{count, CrudData, CrudEditMode, CrudRow, PAGE_SIZE};
+
+#[derive(Default, Eq, PartialEq, Hash, Debug, Copy, Clone)]
+enum TagField {
+    #[default]
+    Name = 0,
+}
+
+impl TagField {
+    fn next(&self) -> Self {
+        match *self {
+            TagField::Name => TagField::Name,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TagFieldError;
+impl TryFrom<usize> for TagField {
+    type Error = TagFieldError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TagField::Name),
+            _ => Err(TagFieldError),
+        }
+    }
+}
+
+impl Display for TagField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Default)]
+pub struct Tags {
+    db: Option<DatabaseConnection>,
+    tags: Vec<Tag>,
+    order: Option<Order>,
+    /// First loaded page, past 0 after jumping to the end.
+    first: u64,
+    page: u64,
+    total: u64,
+}
+
+impl Tags {
+    fn query(&self) -> Select<TagEntity> {
+        let mut query = TagEntity::find();
+        if let Some(order) = &self.order {
+            query = query.order_by(TagColumn::Name, order.clone());
+        }
+        query.order_by_asc(TagColumn::Id)
+    }
+}
+
+/// Delete the tags `ids`, unlinking them from their posts first. Returns how many were
+/// deleted.
+async fn delete_tags<C: ConnectionTrait>(cnx: &C, ids: &[i32]) -> Result<u64> {
+    PostTagEntity::delete_many()
+        .filter(PostTagColumn::TagId.is_in(ids.iter().copied()))
+        .exec(cnx)
+        .await?;
+    Ok(TagEntity::delete_many()
+        .filter(TagColumn::Id.is_in(ids.iter().copied()))
+        .exec(cnx)
+        .await?
+        .rows_affected)
+}
+
+#[async_trait]
+impl CrudData for Tags {
+    fn headers(&self) -> Vec<String> {
+        vec![TagField::Name.to_string()]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.tags.iter().map(|x| vec![x.name.clone()]).collect()
+    }
+
+    fn widths(&self) -> Vec<Constraint> {
+        vec![Constraint::Percentage(100)]
+    }
+
+    fn num_rows(&self) -> usize {
+        self.tags.len()
+    }
+
+    fn set_db(&mut self, cnx: Option<DatabaseConnection>) {
+        self.db = cnx;
+    }
+
+    async fn delete(&self, idx: usize) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let txn = cnx.begin().await?;
+            delete_tags(&txn, &[self.tags[idx].id]).await?;
+            txn.commit().await?;
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn describe(&self, idx: usize) -> Result<String> {
+        let tag = &self.tags[idx];
+        if let Some(cnx) = &self.db {
+            let posts = PostTagEntity::find()
+                .filter(PostTagColumn::TagId.eq(tag.id))
+                .count(cnx)
+                .await?;
+            Ok(format!(
+                "tag \"{}\", set on {}",
+                tag.name,
+                count(posts, "post")
+            ))
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn describe_bulk(&self, ids: &[i32]) -> Result<String> {
+        if let Some(cnx) = &self.db {
+            let posts = PostTagEntity::find()
+                .select_only()
+                .column(PostTagColumn::PostId)
+                .distinct()
+                .filter(PostTagColumn::TagId.is_in(ids.iter().copied()))
+                .count(cnx)
+                .await?;
+            Ok(format!(
+                "{}, set on {}",
+                count(ids.len() as u64, "tag"),
+                count(posts, "post")
+            ))
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        self.tags.clear();
+        self.first = 0;
+        self.page = 0;
+        self.load_more().await?;
+        Ok(())
+    }
+
+    async fn load_more(&mut self) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            let paginator = self.query().paginate(cnx, PAGE_SIZE);
+            if self.page == 0 {
+                self.total = paginator.num_items().await?;
+            }
+            let tags = paginator.fetch_page(self.page).await?;
+            if tags.is_empty() {
+                // Rows were deleted since the count, stop asking for more.
+                self.total = self.first * PAGE_SIZE + self.tags.len() as u64;
+                return Ok(false);
+            }
+            self.page += 1;
+            self.tags.extend(tags);
+            Ok(true)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_last(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let paginator = self.query().paginate(cnx, PAGE_SIZE);
+            self.total = paginator.num_items().await?;
+            self.first = self.total.saturating_sub(1) / PAGE_SIZE;
+            self.page = self.first + 1;
+            self.tags = paginator.fetch_page(self.first).await?;
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_previous(&mut self) -> Result<usize> {
+        if self.first == 0 {
+            return Ok(0);
+        }
+        if let Some(cnx) = &self.db {
+            let tags = self
+                .query()
+                .paginate(cnx, PAGE_SIZE)
+                .fetch_page(self.first - 1)
+                .await?;
+            self.first -= 1;
+            let len = tags.len();
+            self.tags.splice(0..0, tags);
+            Ok(len)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_id(&mut self, id: i32) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            self.tags = TagEntity::find_by_id(id)
+                .one(cnx)
+                .await?
+                .into_iter()
+                .collect();
+            Ok(!self.tags.is_empty())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    fn first_row(&self) -> usize {
+        (self.first * PAGE_SIZE) as usize
+    }
+
+    fn total_rows(&self) -> usize {
+        self.total as usize
+    }
+
+    fn set_order(&mut self, column: usize, order: Option<Order>) -> bool {
+        if column != 0 {
+            return false;
+        }
+        self.order = order;
+        true
+    }
+
+    fn to_db_id(&self, idx: usize) -> i32 {
+        self.tags[idx].id
+    }
+
+    fn record(&self, idx: usize) -> Result<JsonValue> {
+        Ok(serde_json::to_value(&self.tags[idx])?)
+    }
+
+    async fn bulk(&mut self, ids: &[i32], change: &Bulk) -> Result<String> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        if *change != Bulk::Delete {
+            return Err(eyre!("Tags can only be deleted in bulk"));
+        }
+        let txn = cnx.begin().await?;
+        let deleted = delete_tags(&txn, ids).await?;
+        txn.commit().await?;
+        Ok(format!("Deleted {}", count(deleted, "tag")))
+    }
+}
+
+#[derive(Default)]
+pub struct TagEdit {
+    mode: CrudEditMode,
+    db: Option<DatabaseConnection>,
+    row: Option<Tag>,
+    focused_field: TagField,
+    fields: HashMap<TagField, Input>,
+}
+
+#[async_trait]
+impl CrudRow for TagEdit {
+    async fn edit(&mut self, idx: i32) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            self.row = TagEntity::find_by_id(idx).one(cnx).await?;
+            if let Some(tag) = &self.row {
+                self.fields.insert(TagField::Name, Input::new(&tag.name));
+                self.mode = CrudEditMode::Edit;
+                self.focused_field = TagField::Name;
+            }
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn new(&mut self) -> Result<()> {
+        self.fields.insert(TagField::Name, Input::default());
+        self.focused_field = TagField::Name;
+        self.row = None;
+        self.mode = CrudEditMode::New;
+        Ok(())
+    }
+
+    async fn save(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let mut tag: ActiveTag = match self.mode {
+                CrudEditMode::New => ActiveModelTrait::default(),
+                CrudEditMode::Edit => {
+                    if let Some(tag) = &self.row {
+                        tag.clone().into()
+                    } else {
+                        return Err(eyre!("Edit mode with no row"));
+                    }
+                }
+            };
+            tag.name = Set(self
+                .fields
+                .get(&TagField::Name)
+                .map_or("", Input::value)
+                .trim()
+                .to_owned());
+
+            self.row = Some(match self.mode {
+                CrudEditMode::Edit => tag.update(cnx).await?,
+                CrudEditMode::New => tag.insert(cnx).await?,
+            });
+            self.mode = CrudEditMode::Edit;
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.row.as_ref().map(|x| x.id)
+    }
+
+    fn values(&self) -> Vec<String> {
+        vec![self
+            .fields
+            .get(&TagField::Name)
+            .map_or("", Input::value)
+            .to_string()]
+    }
+
+    fn set_field(&mut self, name: &str, value: &str) -> Result<()> {
+        let field = (0..1)
+            .map(|x| TagField::try_from(x).unwrap())
+            .find(|x| x.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| eyre!("Unknown field \"{}\"", name))?;
+        self.fields.insert(field, Input::new(value));
+        Ok(())
+    }
+
+    fn focus_next_field(&mut self) {
+        self.focused_field = self.focused_field.next();
+    }
+
+    fn focused_input(&mut self) -> Option<&mut Input> {
+        self.fields.get_mut(&self.focused_field)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        for i in 0..1 {
+            let field = TagField::try_from(i).unwrap();
+            let style = if field == self.focused_field {
+                FormStyle::highlighted()
+            } else {
+                FormStyle::normal()
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(field.to_string())
+                .style(style);
+            let focused = field == self.focused_field;
+            self.fields
+                .entry(field)
+                .or_default()
+                .draw(f, layout[i], block, focused);
+        }
+        Ok(())
+    }
+
+    fn set_db(&mut self, db: Option<DatabaseConnection>) {
+        self.db = db;
+    }
+}
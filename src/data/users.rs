@@ -1,25 +1,80 @@
 use std::{collections::HashMap, fmt::Display};
 
 use async_trait::async_trait;
+use blogapi::models::_entities::posts::{Column as PostColumn, Entity as PostEntity};
+use blogapi::models::_entities::users::Column as UserColumn;
 use blogapi::models::users::{
     ActiveModel as ActiveUser, Entity as UserEntity, Model as User, RegisterParams,
 };
-use color_eyre::{eyre::eyre, Result};
+use color_eyre::{
+    eyre::{eyre, Report},
+    Result,
+};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders},
     Frame,
 };
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, ModelTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, ModelTrait,
+    Order, PaginatorTrait, QueryFilter, QueryOrder, Select, Set, TransactionTrait,
+};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    action::Bulk, components::crudedit::CrudEdit, style::FormStyle, widgets::input::Input,
+};
 
-use crate::{components::crudedit::CrudEdit, style::FormStyle};
+use super::{
+    count,
+    validation::{self, email, required},
+    CrudData, CrudEditMode, CrudRow, PAGE_SIZE,
+};
 
-use super::{CrudData, CrudEditMode, CrudRow};
+const SECRET_FIELDS: &[&str] = &[
+    "password",
+    "api_key",
+    "reset_token",
+    "email_verification_token",
+];
 
 #[derive(Default)]
 pub struct Users {
     db: Option<DatabaseConnection>,
     users: Vec<User>,
+    order: Option<(UserColumn, Order)>,
+    /// First loaded page, past 0 after jumping to the end.
+    first: u64,
+    page: u64,
+    total: u64,
+}
+
+impl Users {
+    fn query(&self) -> Select<UserEntity> {
+        let mut query = UserEntity::find();
+        if let Some((column, order)) = &self.order {
+            query = query.order_by(*column, order.clone());
+        }
+        query.order_by_asc(UserColumn::Id)
+    }
+}
+
+/// Number of posts written by the users `ids`. Users are not deleted while they have any, as
+/// the posts would be left without an author.
+async fn authored<C: ConnectionTrait>(cnx: &C, ids: &[i32]) -> Result<u64> {
+    Ok(PostEntity::find()
+        .filter(PostColumn::UserId.is_in(ids.iter().copied()))
+        .count(cnx)
+        .await?)
+}
+
+/// Why `users`, who wrote `posts` posts, cannot be deleted.
+fn has_posts(users: &str, posts: u64) -> Report {
+    eyre!(
+        "{} wrote {}, set another author on them before deleting",
+        users,
+        count(posts, "post")
+    )
 }
 
 #[async_trait]
@@ -48,25 +103,173 @@ impl CrudData for Users {
     }
 
     async fn delete(&self, idx: usize) -> Result<()> {
+        let user = &self.users[idx];
         if let Some(cnx) = &self.db {
-            self.users[idx].clone().delete(cnx).await?;
+            let txn = cnx.begin().await?;
+            let posts = authored(&txn, &[user.id]).await?;
+            if posts > 0 {
+                return Err(has_posts(&format!("User {}", user.email), posts));
+            }
+            user.clone().delete(&txn).await?;
+            txn.commit().await?;
         }
         Ok(())
     }
 
-    async fn refresh(&mut self) -> Result<()> {
+    /// Fails for a user who wrote posts, rather than asking to delete them.
+    async fn describe(&self, idx: usize) -> Result<String> {
+        let user = &self.users[idx];
+        if let Some(cnx) = &self.db {
+            let posts = authored(cnx, &[user.id]).await?;
+            if posts > 0 {
+                return Err(has_posts(&format!("User {}", user.email), posts));
+            }
+            Ok(format!("user {}", user.email))
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    /// Fails if any of the users wrote posts, rather than asking to delete them.
+    async fn describe_bulk(&self, ids: &[i32]) -> Result<String> {
         if let Some(cnx) = &self.db {
-            self.users = UserEntity::find().all(cnx).await?;
+            let posts = authored(cnx, ids).await?;
+            if posts > 0 {
+                return Err(has_posts("The marked users", posts));
+            }
+            Ok(count(ids.len() as u64, "user"))
+        } else {
+            Err(eyre!("Database is not connected"))
         }
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        self.users.clear();
+        self.first = 0;
+        self.page = 0;
+        self.load_more().await?;
         Ok(())
     }
 
+    async fn load_more(&mut self) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            let paginator = self.query().paginate(cnx, PAGE_SIZE);
+            if self.page == 0 {
+                self.total = paginator.num_items().await?;
+            }
+            let users = paginator.fetch_page(self.page).await?;
+            if !users.is_empty() {
+                self.page += 1;
+                self.users.extend(users);
+                return Ok(true);
+            }
+            self.total = self.first * PAGE_SIZE + self.users.len() as u64;
+        }
+        Ok(false)
+    }
+
+    async fn load_last(&mut self) -> Result<()> {
+        if let Some(cnx) = &self.db {
+            let paginator = self.query().paginate(cnx, PAGE_SIZE);
+            self.total = paginator.num_items().await?;
+            self.first = self.total.saturating_sub(1) / PAGE_SIZE;
+            self.page = self.first + 1;
+            self.users = paginator.fetch_page(self.first).await?;
+            Ok(())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_previous(&mut self) -> Result<usize> {
+        if self.first == 0 {
+            return Ok(0);
+        }
+        if let Some(cnx) = &self.db {
+            let users = self
+                .query()
+                .paginate(cnx, PAGE_SIZE)
+                .fetch_page(self.first - 1)
+                .await?;
+            self.first -= 1;
+            let len = users.len();
+            self.users.splice(0..0, users);
+            Ok(len)
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    async fn load_id(&mut self, id: i32) -> Result<bool> {
+        if let Some(cnx) = &self.db {
+            self.users = UserEntity::find_by_id(id)
+                .one(cnx)
+                .await?
+                .into_iter()
+                .collect();
+            Ok(!self.users.is_empty())
+        } else {
+            Err(eyre!("Database is not connected"))
+        }
+    }
+
+    fn first_row(&self) -> usize {
+        (self.first * PAGE_SIZE) as usize
+    }
+
+    fn total_rows(&self) -> usize {
+        self.total as usize
+    }
+
+    fn set_order(&mut self, column: usize, order: Option<Order>) -> bool {
+        let column = match column {
+            0 => UserColumn::Name,
+            1 => UserColumn::Email,
+            _ => return false,
+        };
+        self.order = order.map(|x| (column, x));
+        true
+    }
+
     fn to_db_id(&self, idx: usize) -> i32 {
         self.users[idx].id
     }
+
+    fn record(&self, idx: usize) -> Result<JsonValue> {
+        let mut record = serde_json::to_value(&self.users[idx])?;
+        // Never export credentials, whatever the model serializes.
+        if let Some(fields) = record.as_object_mut() {
+            for secret in SECRET_FIELDS {
+                fields.remove(*secret);
+            }
+        }
+        Ok(record)
+    }
+
+    async fn bulk(&mut self, ids: &[i32], change: &Bulk) -> Result<String> {
+        let cnx = self
+            .db
+            .as_ref()
+            .ok_or_else(|| eyre!("Database is not connected"))?;
+        if *change != Bulk::Delete {
+            return Err(eyre!("Users can only be deleted in bulk"));
+        }
+        let txn = cnx.begin().await?;
+        let posts = authored(&txn, ids).await?;
+        if posts > 0 {
+            return Err(has_posts("The marked users", posts));
+        }
+        let deleted = UserEntity::delete_many()
+            .filter(UserColumn::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        txn.commit().await?;
+        Ok(format!("Deleted {}", count(deleted, "user")))
+    }
 }
 
-#[derive(Default, Debug, Hash, PartialEq, Eq)]
+#[derive(Default, Debug, Hash, PartialEq, Eq, Copy, Clone)]
 enum UserField {
     #[default]
     Name,
@@ -114,7 +317,29 @@ pub struct UserEdit {
     db: Option<DatabaseConnection>,
     row: Option<User>,
     focused_field: UserField,
-    fields: HashMap<UserField, String>,
+    fields: HashMap<UserField, Input>,
+    errors: HashMap<UserField, String>,
+}
+
+impl UserEdit {
+    fn value(&self, field: UserField) -> &str {
+        self.fields.get(&field).map_or("", Input::value)
+    }
+
+    /// Why the value of `field` cannot be saved, if it cannot.
+    fn error(&self, field: UserField) -> Option<String> {
+        let value = self.value(field);
+        match field {
+            UserField::Name => required(value),
+            UserField::Email => required(value).or_else(|| email(value)),
+            // An existing user keeps their password unless a new one is typed.
+            UserField::Password1 if matches!(self.mode, CrudEditMode::New) => required(value),
+            UserField::Password2 if value != self.value(UserField::Password1) => {
+                Some("Passwords do not match".to_string())
+            }
+            _ => None,
+        }
+    }
 }
 
 #[async_trait]
@@ -123,11 +348,15 @@ impl CrudRow for UserEdit {
         if let Some(cnx) = &self.db {
             self.row = UserEntity::find_by_id(idx).one(cnx).await?;
             if let Some(user) = &self.row {
-                self.fields.insert(UserField::Name, user.name.clone());
-                self.fields.insert(UserField::Email, user.email.clone());
-                self.fields.insert(UserField::Password1, String::new());
-                self.fields.insert(UserField::Password2, String::new());
+                self.fields.insert(UserField::Name, Input::new(&user.name));
+                self.fields
+                    .insert(UserField::Email, Input::new(&user.email));
+                self.fields
+                    .insert(UserField::Password1, Input::default().secret(true));
+                self.fields
+                    .insert(UserField::Password2, Input::default().secret(true));
                 self.mode = CrudEditMode::Edit;
+                self.errors.clear();
                 return Ok(());
             }
 
@@ -137,21 +366,26 @@ impl CrudRow for UserEdit {
         }
     }
 
-    fn new(&mut self) {
-        self.fields.insert(UserField::Name, String::new());
-        self.fields.insert(UserField::Email, String::new());
-        self.fields.insert(UserField::Password1, String::new());
-        self.fields.insert(UserField::Password2, String::new());
+    async fn new(&mut self) -> Result<()> {
+        self.fields.insert(UserField::Name, Input::default());
+        self.fields.insert(UserField::Email, Input::default());
+        self.fields
+            .insert(UserField::Password1, Input::default().secret(true));
+        self.fields
+            .insert(UserField::Password2, Input::default().secret(true));
+        self.row = None;
         self.mode = CrudEditMode::New;
+        self.errors.clear();
+        Ok(())
     }
 
     async fn save(&mut self) -> Result<()> {
-        let password =
-            if self.fields.get(&UserField::Password1) == self.fields.get(&UserField::Password2) {
-                self.fields.get(&UserField::Password1).clone()
-            } else {
-                None
-            };
+        let password = self.fields.get(&UserField::Password1).map(Input::value);
+        let password = if password == self.fields.get(&UserField::Password2).map(Input::value) {
+            password
+        } else {
+            None
+        };
         if let Some(cnx) = &self.db {
             if let Some(pw) = password {
                 match self.mode {
@@ -162,39 +396,40 @@ impl CrudRow for UserEdit {
                                 name: self
                                     .fields
                                     .get(&UserField::Name)
-                                    .unwrap_or(&"".to_string())
-                                    .clone(),
+                                    .map_or("", Input::value)
+                                    .to_string(),
                                 email: self
                                     .fields
                                     .get(&UserField::Email)
-                                    .unwrap_or(&"".to_string())
-                                    .clone(),
+                                    .map_or("", Input::value)
+                                    .to_string(),
                                 password: pw.to_string(),
                             },
                         )
                         .await?;
                         let mutuser: ActiveUser = user.into();
-                        mutuser.verified(cnx).await?;
+                        self.row = Some(mutuser.verified(cnx).await?);
+                        self.mode = CrudEditMode::Edit;
                     }
                     CrudEditMode::Edit => {
                         if let Some(user) = &self.row {
                             let mut mutuser: ActiveUser = user.clone().into();
 
-                            if !user.verify_password(pw) {
+                            if !pw.is_empty() && !user.verify_password(pw) {
                                 mutuser.clone().reset_password(cnx, pw).await?;
                             }
 
                             mutuser.name = Set(self
                                 .fields
                                 .get(&UserField::Name)
-                                .unwrap_or(&"".to_string())
-                                .clone());
+                                .map_or("", Input::value)
+                                .to_string());
                             mutuser.email = Set(self
                                 .fields
                                 .get(&UserField::Email)
-                                .unwrap_or(&"".to_string())
-                                .clone());
-                            mutuser.update(cnx).await?;
+                                .map_or("", Input::value)
+                                .to_string());
+                            self.row = Some(mutuser.update(cnx).await?);
                         }
                     }
                 }
@@ -203,20 +438,52 @@ impl CrudRow for UserEdit {
         Ok(())
     }
 
-    fn focus_next_field(&mut self) {
-        self.focused_field = self.focused_field.next();
+    fn validate(&mut self) -> Result<()> {
+        let errors: Vec<(UserField, String)> = (0..4)
+            .map(|x| UserField::try_from(x).unwrap())
+            .filter_map(|x| self.error(x).map(|e| (x, e)))
+            .collect();
+        let summary: Vec<(String, String)> = errors
+            .iter()
+            .map(|(field, e)| (field.to_string(), e.clone()))
+            .collect();
+        self.errors = errors.into_iter().collect();
+        validation::check(&summary)
     }
 
-    fn input(&mut self, c: char) {
-        if let Some(field) = self.fields.get_mut(&self.focused_field) {
-            field.push(c);
-        }
+    fn id(&self) -> Option<i32> {
+        self.row.as_ref().map(|x| x.id)
     }
 
-    fn delete_last_char(&mut self) {
-        if let Some(field) = self.fields.get_mut(&self.focused_field) {
-            field.pop();
+    fn values(&self) -> Vec<String> {
+        (0..4)
+            .map(|x| self.value(UserField::try_from(x).unwrap()).to_string())
+            .collect()
+    }
+
+    fn set_field(&mut self, name: &str, value: &str) -> Result<()> {
+        if name.eq_ignore_ascii_case("password") {
+            self.fields
+                .insert(UserField::Password1, Input::new(value).secret(true));
+            self.fields
+                .insert(UserField::Password2, Input::new(value).secret(true));
+            return Ok(());
         }
+        let field = (0..4)
+            .map(|x| UserField::try_from(x).unwrap())
+            .find(|x| x.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| eyre!("Unknown field \"{}\"", name))?;
+        let secret = matches!(field, UserField::Password1 | UserField::Password2);
+        self.fields.insert(field, Input::new(value).secret(secret));
+        Ok(())
+    }
+
+    fn focus_next_field(&mut self) {
+        self.focused_field = self.focused_field.next();
+    }
+
+    fn focused_input(&mut self) -> Option<&mut Input> {
+        self.fields.get_mut(&self.focused_field)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
@@ -241,16 +508,11 @@ impl CrudRow for UserEdit {
                 .borders(Borders::ALL)
                 .title(field.to_string())
                 .style(style);
-            let value = match field {
-                UserField::Password1 | UserField::Password2 => Paragraph::new(str::repeat(
-                    "*",
-                    self.fields.get(&field).unwrap_or(&"".to_string()).len(),
-                ))
-                .block(block),
-                _ => Paragraph::new(self.fields.get(&field).unwrap_or(&"".to_string()).clone())
-                    .block(block),
-            };
-            f.render_widget(value, layout[i]);
+            let block = validation::with_error(block, self.errors.get(&field));
+            let focused = field == self.focused_field;
+            if let Some(input) = self.fields.get_mut(&field) {
+                input.draw(f, layout[i], block, focused);
+            }
         }
         Ok(())
     }
@@ -0,0 +1,118 @@
+//! Rules checking the fields of [`CrudRow`](super::CrudRow) forms. Each rule returns the
+//! message to show under the field when its value is invalid.
+
+use chrono::NaiveDateTime;
+use color_eyre::{eyre::eyre, Result};
+use ratatui::{
+    text::{Line, Span},
+    widgets::{
+        block::{Position, Title},
+        Block,
+    },
+};
+
+use crate::style::FormStyle;
+
+pub fn required(value: &str) -> Option<String> {
+    value.trim().is_empty().then(|| "Required".to_string())
+}
+
+pub fn max_length(value: &str, max: usize) -> Option<String> {
+    (value.chars().count() > max).then(|| format!("At most {} characters", max))
+}
+
+/// A date and time written in `format`, as understood by [`chrono`].
+pub fn datetime(value: &str, format: &str) -> Option<String> {
+    NaiveDateTime::parse_from_str(value.trim(), format)
+        .is_err()
+        .then(|| format!("Expected a date as {}", chrono_format_example(format)))
+}
+
+/// `format` with its fields written out, as `%Y-%m-%d` gives `YYYY-MM-DD`.
+fn chrono_format_example(format: &str) -> String {
+    [
+        ("%Y", "YYYY"),
+        ("%m", "MM"),
+        ("%d", "DD"),
+        ("%H", "HH"),
+        ("%M", "MM"),
+        ("%S", "SS"),
+    ]
+    .iter()
+    .fold(format.to_string(), |x, (from, to)| x.replace(from, to))
+}
+
+/// A single address with a local part and a dotted domain, which is as far as checking an
+/// email address without sending it a message goes.
+pub fn email(value: &str) -> Option<String> {
+    let valid = match value.trim().split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|x| !x.is_empty())
+                && !value.trim().contains(char::is_whitespace)
+        }
+        None => false,
+    };
+    (!valid).then(|| "Not an email address".to_string())
+}
+
+/// Fail with the `errors` of a form, as field and message pairs, unless there are none.
+pub fn check(errors: &[(String, String)]) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let errors: Vec<String> = errors
+        .iter()
+        .map(|(field, message)| format!("{}: {}", field, message))
+        .collect();
+    Err(eyre!("{}", errors.join(", ")))
+}
+
+/// `block` with `error`, if there is one, written on its bottom border.
+pub fn with_error<'a>(block: Block<'a>, error: Option<&String>) -> Block<'a> {
+    match error {
+        Some(error) => block.title(
+            Title::from(Line::from(Span::styled(error.clone(), FormStyle::error())))
+                .position(Position::Bottom),
+        ),
+        None => block,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_required() {
+        assert_eq!(required("  "), Some("Required".to_string()));
+        assert_eq!(required("x"), None);
+    }
+
+    #[test]
+    fn test_email() {
+        assert_eq!(email("jane@example.com"), None);
+        for invalid in [
+            "jane",
+            "@example.com",
+            "jane@example",
+            "jane@exa mple.com",
+            "a@b@c.d",
+        ] {
+            assert!(email(invalid).is_some(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_datetime() {
+        assert_eq!(datetime("2024-02-29 13:05", "%Y-%m-%d %H:%M"), None);
+        assert_eq!(
+            datetime("2023-02-29 13:05", "%Y-%m-%d %H:%M"),
+            Some("Expected a date as YYYY-MM-DD HH:MM".to_string())
+        );
+    }
+}
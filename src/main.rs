@@ -6,6 +6,7 @@ pub mod action;
 pub mod app;
 pub mod area;
 pub mod cli;
+pub mod commands;
 pub mod components;
 pub mod config;
 pub mod data;
@@ -13,6 +14,7 @@ pub mod mode;
 pub mod style;
 pub mod tui;
 pub mod utils;
+pub mod widgets;
 
 use clap::Parser;
 use cli::Cli;
@@ -20,6 +22,7 @@ use color_eyre::eyre::Result;
 
 use crate::{
     app::App,
+    commands::exit_code,
     utils::{initialize_logging, initialize_panic_handler, version},
 };
 
@@ -29,6 +32,14 @@ async fn tokio_main() -> Result<()> {
     initialize_panic_handler()?;
 
     let args = Cli::parse();
+    if let Some(command) = args.command {
+        if let Err(e) = command.run().await {
+            eprintln!("{} error: {:#}", env!("CARGO_PKG_NAME"), e);
+            std::process::exit(exit_code(&e));
+        }
+        return Ok(());
+    }
+
     let mut app = App::new(args.tick_rate, args.frame_rate)?;
     app.run().await?;
 
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 pub enum Mode {
     #[default]
     Posts,
+    Schedule,
     Tags,
     Users,
 }
@@ -18,8 +19,9 @@ impl TryFrom<usize> for Mode {
     fn try_from(value: usize) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(Mode::Posts),
-            1 => Ok(Mode::Tags),
-            2 => Ok(Mode::Users),
+            1 => Ok(Mode::Schedule),
+            2 => Ok(Mode::Tags),
+            3 => Ok(Mode::Users),
             _ => Err(InvalidValue),
         }
     }
@@ -28,7 +30,8 @@ impl TryFrom<usize> for Mode {
 impl Mode {
     pub fn next(&self) -> Self {
         match *self {
-            Mode::Posts => Mode::Tags,
+            Mode::Posts => Mode::Schedule,
+            Mode::Schedule => Mode::Tags,
             Mode::Tags => Mode::Users,
             Mode::Users => Mode::Posts,
         }
@@ -24,6 +24,10 @@ impl TableStyle {
     pub(crate) fn header() -> Style {
         Style::new().fg(Color::LightGreen)
     }
+
+    pub(crate) fn marked() -> Style {
+        Style::new().fg(Color::Cyan).bold()
+    }
 }
 
 pub(crate) struct FormStyle;
@@ -35,4 +39,53 @@ impl FormStyle {
     pub(crate) fn highlighted() -> Style {
         Self::normal().fg(Color::Blue)
     }
+
+    pub(crate) fn error() -> Style {
+        Style::new().fg(Color::Red)
+    }
+}
+
+pub(crate) struct MarkdownStyle;
+impl MarkdownStyle {
+    pub(crate) fn heading() -> Style {
+        Style::new().fg(Color::LightGreen).bold()
+    }
+
+    pub(crate) fn code() -> Style {
+        Style::new().fg(Color::Yellow)
+    }
+
+    pub(crate) fn link() -> Style {
+        Style::new().fg(Color::LightBlue).underlined()
+    }
+
+    pub(crate) fn url() -> Style {
+        Style::new().fg(Color::DarkGray)
+    }
+
+    pub(crate) fn quote() -> Style {
+        Style::new().fg(Color::DarkGray)
+    }
+
+    pub(crate) fn bullet() -> Style {
+        Style::new().fg(Color::LightGreen)
+    }
+}
+
+pub(crate) struct DiffStyle;
+impl DiffStyle {
+    pub(crate) fn removed() -> Style {
+        Style::new().fg(Color::Red)
+    }
+
+    pub(crate) fn added() -> Style {
+        Style::new().fg(Color::Green)
+    }
+}
+
+pub(crate) struct ScheduleStyle;
+impl ScheduleStyle {
+    pub(crate) fn overdue() -> Style {
+        Style::new().fg(Color::Yellow)
+    }
 }
@@ -0,0 +1,4 @@
+pub mod datetime;
+pub mod input;
+pub mod markdown;
+pub mod picker;
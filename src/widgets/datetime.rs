@@ -0,0 +1,176 @@
+use chrono::{Datelike, Duration, Months, NaiveDateTime, Timelike};
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/// Width of the year, month, day, hour and minute parts, in that order.
+const PARTS: [usize; 5] = [4, 2, 2, 2, 2];
+
+/// A date and time edited one part at a time.
+///
+/// `Left`/`Right` move between the year, month, day, hour and minute, `Up`/`Down` step the
+/// focused part, carrying over to the others, and typing digits replaces it once there are
+/// enough of them for a valid value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTimeInput {
+    value: NaiveDateTime,
+    /// Focused part, as an index in [`PARTS`].
+    part: usize,
+    /// Digits typed in the focused part so far.
+    typed: String,
+}
+
+impl DateTimeInput {
+    pub fn new(value: NaiveDateTime) -> Self {
+        DateTimeInput {
+            value,
+            part: 0,
+            typed: String::new(),
+        }
+    }
+
+    pub fn value(&self) -> NaiveDateTime {
+        self.value
+    }
+
+    pub fn left(&mut self) {
+        self.typed.clear();
+        self.part = self.part.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.typed.clear();
+        self.part = (self.part + 1).min(PARTS.len() - 1);
+    }
+
+    pub fn up(&mut self) {
+        self.step(1);
+    }
+
+    pub fn down(&mut self) {
+        self.step(-1);
+    }
+
+    /// Move the focused part by `delta`. Months that are too short for the day end on their
+    /// last day.
+    fn step(&mut self, delta: i64) {
+        self.typed.clear();
+        let months = |n: i64| Months::new(n.unsigned_abs() as u32);
+        let value = match self.part {
+            0 | 1 => {
+                let n = if self.part == 0 { delta * 12 } else { delta };
+                if n >= 0 {
+                    self.value.checked_add_months(months(n))
+                } else {
+                    self.value.checked_sub_months(months(n))
+                }
+            }
+            2 => self.value.checked_add_signed(Duration::days(delta)),
+            3 => self.value.checked_add_signed(Duration::hours(delta)),
+            _ => self.value.checked_add_signed(Duration::minutes(delta)),
+        };
+        if let Some(value) = value {
+            self.value = value;
+        }
+    }
+
+    /// Type digit `c` in the focused part, which is set and left once it is complete.
+    pub fn input(&mut self, c: char) {
+        if !c.is_ascii_digit() {
+            return;
+        }
+        self.typed.push(c);
+        if self.typed.len() < PARTS[self.part] {
+            return;
+        }
+        let n: u32 = self.typed.parse().unwrap_or_default();
+        let value = match self.part {
+            0 => self.value.with_year(n as i32),
+            1 => self.value.with_month(n),
+            2 => self.value.with_day(n),
+            3 => self.value.with_hour(n),
+            _ => self.value.with_minute(n),
+        };
+        self.typed.clear();
+        if let Some(value) = value {
+            self.value = value;
+            self.right();
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.typed.pop();
+    }
+
+    /// Draw the date in `block`, with the focused part highlighted when `focused`.
+    pub fn draw(&self, f: &mut Frame<'_>, area: Rect, block: Block<'_>, focused: bool) {
+        let parts = [
+            format!("{:04}", self.value.year()),
+            format!("{:02}", self.value.month()),
+            format!("{:02}", self.value.day()),
+            format!("{:02}", self.value.hour()),
+            format!("{:02}", self.value.minute()),
+        ];
+        let mut spans = Vec::new();
+        for (i, (part, separator)) in parts.into_iter().zip(["-", "-", " ", ":", ""]).enumerate() {
+            if focused && i == self.part {
+                let part = if self.typed.is_empty() {
+                    part
+                } else {
+                    format!("{:_<width$}", self.typed, width = PARTS[i])
+                };
+                spans.push(Span::raw(part).reversed());
+            } else {
+                spans.push(Span::raw(part));
+            }
+            spans.push(Span::raw(separator));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_step_carries_over() {
+        let mut input = DateTimeInput::new(date(2024, 1, 31, 23, 59));
+        input.right();
+        input.up();
+        assert_eq!(input.value(), date(2024, 2, 29, 23, 59));
+        input.right();
+        input.right();
+        input.right();
+        input.up();
+        assert_eq!(input.value(), date(2024, 3, 1, 0, 0));
+    }
+
+    #[test]
+    fn test_type_parts() {
+        let mut input = DateTimeInput::new(date(2024, 1, 31, 12, 0));
+        "2025".chars().for_each(|c| input.input(c));
+        assert_eq!(input.part, 1);
+        // There is no 31st of February, the month is left as it was.
+        "02".chars().for_each(|c| input.input(c));
+        assert_eq!(input.value(), date(2025, 1, 31, 12, 0));
+        assert_eq!(input.part, 1);
+        "12".chars().for_each(|c| input.input(c));
+        assert_eq!(input.value(), date(2025, 12, 31, 12, 0));
+        assert_eq!(input.part, 2);
+    }
+}
@@ -0,0 +1,584 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/// An editable text with a cursor, on a single line unless it is `multiline`.
+///
+/// Besides typing and `Backspace`/`Delete`, it supports moving by char with `Left`/`Right`,
+/// by word with `Ctrl`/`Alt` + `Left`/`Right` or `Alt-b`/`Alt-f`, to the start or end of the
+/// line with `Home`/`End`, and deleting the previous word with `Ctrl-W` or everything before
+/// the cursor on the line with `Ctrl-U`.
+///
+/// A multiline input is soft wrapped at word boundaries, moves between wrapped lines with
+/// `Up`/`Down` and scrolls to keep the cursor in view.
+///
+/// Edits are recorded for [`Input::undo`] and [`Input::redo`]. A run of chars typed, or
+/// deleted, one after the other is a single step.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Input {
+    value: String,
+    /// Position of the cursor, in chars.
+    cursor: usize,
+    multiline: bool,
+    secret: bool,
+    /// Width the text was last wrapped at, 0 before it is first drawn.
+    width: usize,
+    /// First wrapped line shown.
+    scroll: usize,
+    /// Text and cursor before each edit, latest last.
+    undo: Vec<(String, usize)>,
+    /// Text and cursor before each undo, latest last.
+    redo: Vec<(String, usize)>,
+    /// Kind of the last edit and where it left the cursor, which the next edit of the same
+    /// kind continues from when it is at the same place.
+    last_edit: Option<(Edit, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Typing,
+    Deleting,
+    Other,
+}
+
+impl Input {
+    pub fn new(value: &str) -> Self {
+        Input {
+            value: value.to_string(),
+            cursor: value.chars().count(),
+            ..Default::default()
+        }
+    }
+
+    /// Accept line breaks on `Enter`.
+    pub fn multiline(mut self, multiline: bool) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Show a `*` in place of each char.
+    pub fn secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.multiline
+    }
+
+    /// Replace the text and move the cursor to its end.
+    pub fn set_value(&mut self, value: &str) {
+        self.record(Edit::Other);
+        self.value = value.to_string();
+        self.cursor = self.len();
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    fn char_at(&self, cursor: usize) -> Option<char> {
+        self.value.chars().nth(cursor)
+    }
+
+    /// Save the text for undo before an edit of `kind`, unless the edit continues the last one.
+    fn record(&mut self, kind: Edit) {
+        let continued = kind != Edit::Other && self.last_edit == Some((kind, self.cursor));
+        if !continued {
+            self.undo.push((self.value.clone(), self.cursor));
+        }
+        self.redo.clear();
+        self.last_edit = None;
+    }
+
+    /// Note where an edit of `kind` left the cursor, once it is done.
+    fn recorded(&mut self, kind: Edit) {
+        self.last_edit = Some((kind, self.cursor));
+    }
+
+    /// Go back to the text before the last edit. Returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let Some((value, cursor)) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push((self.value.clone(), self.cursor));
+        self.value = value;
+        self.cursor = cursor;
+        self.last_edit = None;
+        true
+    }
+
+    /// Apply again the last edit undone. Returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let Some((value, cursor)) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push((self.value.clone(), self.cursor));
+        self.value = value;
+        self.cursor = cursor;
+        self.last_edit = None;
+        true
+    }
+
+    /// Remove the chars between the `from` and `to` cursor positions and put the cursor there.
+    fn remove(&mut self, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+        let range = self.byte_index(from)..self.byte_index(to);
+        self.value.replace_range(range, "");
+        self.cursor = from;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        // Each line typed is a step of its own.
+        let kind = if c == '\n' { Edit::Other } else { Edit::Typing };
+        self.record(kind);
+        let i = self.byte_index(self.cursor);
+        self.value.insert(i, c);
+        self.cursor += 1;
+        self.recorded(kind);
+    }
+
+    /// Insert `text` at the cursor, with its line breaks turned into spaces unless the input
+    /// is multiline.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let text = if self.multiline {
+            text
+        } else {
+            text.replace('\n', " ")
+        };
+        self.record(Edit::Other);
+        let i = self.byte_index(self.cursor);
+        self.value.insert_str(i, &text);
+        self.cursor += text.chars().count();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.record(Edit::Deleting);
+            self.remove(self.cursor - 1, self.cursor);
+            self.recorded(Edit::Deleting);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            self.record(Edit::Deleting);
+            self.remove(self.cursor, self.cursor + 1);
+            self.recorded(Edit::Deleting);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    /// Start of the line the cursor is on.
+    fn line_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().take(self.cursor).collect();
+        chars.iter().rposition(|c| *c == '\n').map_or(0, |i| i + 1)
+    }
+
+    /// End of the line the cursor is on, before its line break.
+    fn line_end(&self) -> usize {
+        self.value
+            .chars()
+            .skip(self.cursor)
+            .position(|c| c == '\n')
+            .map_or(self.len(), |i| self.cursor + i)
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = self.line_start();
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.line_end();
+    }
+
+    /// Start of the word before the cursor.
+    fn previous_word(&self) -> usize {
+        let mut i = self.cursor;
+        while i > 0 && self.char_at(i - 1).is_some_and(char::is_whitespace) {
+            i -= 1;
+        }
+        while i > 0 && self.char_at(i - 1).is_some_and(|c| !c.is_whitespace()) {
+            i -= 1;
+        }
+        i
+    }
+
+    /// End of the word after the cursor.
+    fn next_word(&self) -> usize {
+        let len = self.len();
+        let mut i = self.cursor;
+        while i < len && self.char_at(i).is_some_and(char::is_whitespace) {
+            i += 1;
+        }
+        while i < len && self.char_at(i).is_some_and(|c| !c.is_whitespace()) {
+            i += 1;
+        }
+        i
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = self.previous_word();
+    }
+
+    pub fn word_right(&mut self) {
+        self.cursor = self.next_word();
+    }
+
+    /// The text split in lines of at most `width` chars, as ranges of char positions. Lines
+    /// are broken after the last whitespace that fits, or anywhere for longer words, and line
+    /// breaks are left out of the ranges.
+    fn wrap(&self, width: usize) -> Vec<Range<usize>> {
+        let chars: Vec<char> = self.value.chars().collect();
+        let width = width.max(1);
+        let mut lines = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = chars[start..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |i| start + i);
+            while end - start >= width {
+                let next = chars[start..start + width]
+                    .iter()
+                    .rposition(|c| c.is_whitespace())
+                    .map_or(start + width, |i| start + i + 1);
+                lines.push(start..next);
+                start = next;
+            }
+            lines.push(start..end);
+            if end == chars.len() {
+                return lines;
+            }
+            start = end + 1;
+        }
+    }
+
+    /// Wrapped line and column of the cursor in `lines`.
+    fn wrapped_position(&self, lines: &[Range<usize>]) -> (usize, usize) {
+        let line = lines
+            .iter()
+            .rposition(|x| x.start <= self.cursor)
+            .unwrap_or(0);
+        (line, self.cursor - lines[line].start)
+    }
+
+    /// Move the cursor to the same column of the wrapped line above or below, or to the
+    /// start or end of the text from its first or last line.
+    fn move_line(&mut self, down: bool) {
+        let width = if self.width == 0 {
+            usize::MAX
+        } else {
+            self.width
+        };
+        let lines = self.wrap(width);
+        let (line, column) = self.wrapped_position(&lines);
+        let target = if down {
+            line + 1
+        } else if line > 0 {
+            line - 1
+        } else {
+            self.cursor = 0;
+            return;
+        };
+        let Some(range) = lines.get(target) else {
+            self.cursor = self.len();
+            return;
+        };
+        // The end of a soft wrapped line is the start of the next one, stay before it.
+        let last = if lines.get(target + 1).is_some_and(|x| x.start == range.end) {
+            range.end - 1
+        } else {
+            range.end
+        };
+        self.cursor = (range.start + column).min(last);
+    }
+
+    pub fn up(&mut self) {
+        self.move_line(false);
+    }
+
+    pub fn down(&mut self) {
+        self.move_line(true);
+    }
+
+    pub fn delete_word(&mut self) {
+        let start = self.previous_word();
+        if start < self.cursor {
+            self.record(Edit::Other);
+            self.remove(start, self.cursor);
+        }
+    }
+
+    pub fn delete_to_line_start(&mut self) {
+        let start = self.line_start();
+        if start < self.cursor {
+            self.record(Edit::Other);
+            self.remove(start, self.cursor);
+        }
+    }
+
+    /// Apply `key` if it is an editing key. Returns whether it was.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('u') if ctrl => self.delete_to_line_start(),
+            KeyCode::Char('b') if alt => self.word_left(),
+            KeyCode::Char('f') if alt => self.word_right(),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Enter if self.multiline => self.insert('\n'),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left if ctrl || alt => self.word_left(),
+            KeyCode::Right if ctrl || alt => self.word_right(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            KeyCode::Up if self.multiline => self.up(),
+            KeyCode::Down if self.multiline => self.down(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Line and column of the cursor, in chars.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let before: Vec<char> = self.value.chars().take(self.cursor).collect();
+        let line = before.iter().filter(|c| **c == '\n').count();
+        (line, self.cursor - self.line_start())
+    }
+
+    /// Draw the text in `block`, scrolled to keep the cursor in view, and place the terminal
+    /// cursor on it when `focused`.
+    pub fn draw(&mut self, f: &mut Frame<'_>, area: Rect, block: Block<'_>, focused: bool) {
+        let inner = block.inner(area);
+        if self.multiline {
+            self.draw_wrapped(f, area, block, focused);
+            return;
+        }
+        let text = if self.secret {
+            "*".repeat(self.len())
+        } else {
+            self.value.clone()
+        };
+        let (line, column) = self.cursor_position();
+        let (line, column) = (line as u16, column as u16);
+        let scroll = (
+            line.saturating_sub(inner.height.saturating_sub(1)),
+            column.saturating_sub(inner.width.saturating_sub(1)),
+        );
+        f.render_widget(Paragraph::new(text).block(block).scroll(scroll), area);
+        if focused && inner.width > 0 && inner.height > 0 {
+            f.set_cursor(inner.x + column - scroll.1, inner.y + line - scroll.0);
+        }
+    }
+
+    fn draw_wrapped(&mut self, f: &mut Frame<'_>, area: Rect, block: Block<'_>, focused: bool) {
+        let inner = block.inner(area);
+        self.width = usize::from(inner.width);
+        let height = usize::from(inner.height);
+        let lines = self.wrap(self.width);
+        let (line, column) = self.wrapped_position(&lines);
+        if line < self.scroll {
+            self.scroll = line;
+        } else if line >= self.scroll + height {
+            self.scroll = line + 1 - height;
+        }
+
+        let chars: Vec<char> = self.value.chars().collect();
+        let text: Vec<Line> = lines
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|x| Line::from(chars[x.clone()].iter().collect::<String>()))
+            .collect();
+        f.render_widget(Paragraph::new(text).block(block), area);
+        if focused && inner.width > 0 && inner.height > 0 {
+            let x = column.min(self.width - 1) as u16;
+            f.set_cursor(inner.x + x, inner.y + (line - self.scroll) as u16);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_insert_in_the_middle() {
+        let mut input = Input::new("helo");
+        input.left();
+        input.insert('l');
+        assert_eq!(input.value(), "hello");
+        assert_eq!(input.cursor, 4);
+    }
+
+    #[test]
+    fn test_insert_str() {
+        let mut input = Input::new("ad");
+        input.left();
+        input.insert_str("b\r\nc");
+        assert_eq!(input.value(), "ab cd");
+        assert_eq!(input.cursor, 4);
+        let mut input = Input::default().multiline(true);
+        input.insert_str("b\r\nc");
+        assert_eq!(input.value(), "b\nc");
+    }
+
+    #[test]
+    fn test_delete_and_backspace() {
+        let mut input = Input::new("héllo");
+        input.home();
+        input.delete();
+        input.right();
+        input.backspace();
+        assert_eq!(input.value(), "llo");
+    }
+
+    #[test]
+    fn test_word_jumps() {
+        let mut input = Input::new("one two  three");
+        input.word_left();
+        assert_eq!(input.cursor, 9);
+        input.word_left();
+        assert_eq!(input.cursor, 4);
+        input.word_right();
+        assert_eq!(input.cursor, 7);
+    }
+
+    #[test]
+    fn test_ctrl_w_and_ctrl_u() {
+        let mut input = Input::new("first line\nsecond line");
+        assert!(input.handle_key(key(KeyCode::Char('w'), KeyModifiers::CONTROL)));
+        assert_eq!(input.value(), "first line\nsecond ");
+        input.handle_key(key(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(input.value(), "first line\n");
+    }
+
+    #[test]
+    fn test_home_and_end_stay_on_the_line() {
+        let mut input = Input::new("ab\ncd\nef");
+        input.left();
+        input.left();
+        input.left();
+        input.home();
+        assert_eq!(input.cursor_position(), (1, 0));
+        input.end();
+        assert_eq!(input.cursor_position(), (1, 2));
+    }
+
+    #[test]
+    fn test_wrap_at_word_boundaries() {
+        let input = Input::new("the quick brown\nfox");
+        let lines: Vec<String> = input
+            .wrap(8)
+            .into_iter()
+            .map(|x| input.value[x].to_string())
+            .collect();
+        assert_eq!(lines, vec!["the ", "quick ", "brown", "fox"]);
+        let input = Input::new("abcdefgh");
+        assert_eq!(input.wrap(3), vec![0..3, 3..6, 6..8]);
+    }
+
+    #[test]
+    fn test_up_and_down_between_wrapped_lines() {
+        let mut input = Input::new("the quick brown\nfox").multiline(true);
+        input.width = 8;
+        input.up();
+        assert_eq!(input.cursor, 13);
+        input.up();
+        assert_eq!(input.cursor, 7);
+        input.up();
+        assert_eq!(input.cursor, 3);
+        input.up();
+        assert_eq!(input.cursor, 0);
+        input.down();
+        input.down();
+        input.down();
+        assert_eq!(input.cursor_position(), (1, 0));
+    }
+
+    #[test]
+    fn test_enter_only_in_multiline() {
+        let mut input = Input::new("a");
+        assert!(!input.handle_key(key(KeyCode::Enter, KeyModifiers::NONE)));
+        let mut input = input.multiline(true);
+        assert!(input.handle_key(key(KeyCode::Enter, KeyModifiers::NONE)));
+        assert_eq!(input.value(), "a\n");
+    }
+
+    #[test]
+    fn test_undo_typing_at_once() {
+        let mut input = Input::new("a");
+        input.insert('b');
+        input.insert('c');
+        input.backspace();
+        input.backspace();
+        input.left();
+        input.insert('x');
+        assert_eq!(input.value(), "xa");
+        assert!(input.undo());
+        assert_eq!(input.value(), "a");
+        assert!(input.undo());
+        assert_eq!(input.value(), "abc");
+        assert!(input.undo());
+        assert_eq!(input.value(), "a");
+        assert!(!input.undo());
+    }
+
+    #[test]
+    fn test_redo_until_the_next_edit() {
+        let mut input = Input::new("");
+        input.insert_str("one");
+        input.insert_str(" two");
+        input.undo();
+        input.undo();
+        assert!(input.redo());
+        assert_eq!(input.value(), "one");
+        assert_eq!(input.cursor, 3);
+        input.insert('!');
+        assert!(!input.redo());
+        assert_eq!(input.value(), "one!");
+    }
+}
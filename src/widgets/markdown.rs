@@ -0,0 +1,237 @@
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+use ratatui::{
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+};
+
+use crate::style::MarkdownStyle;
+
+/// Render `markdown` as styled text. Headings, emphasis, lists, block quotes, code and links
+/// are styled, anything else is shown as plain text.
+pub fn render(markdown: &str) -> Text<'static> {
+    let mut renderer = Renderer::default();
+    for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+        renderer.event(event);
+    }
+    renderer.flush();
+    while renderer.lines.last().is_some_and(|x| x.spans.is_empty()) {
+        renderer.lines.pop();
+    }
+    Text::from(renderer.lines)
+}
+
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    /// Spans of the line being built.
+    spans: Vec<Span<'static>>,
+    /// Styles of the enclosing tags, innermost last.
+    styles: Vec<Style>,
+    /// Enclosing lists, with the number of their next item when they are ordered.
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    code_block: bool,
+}
+
+impl Renderer {
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |style, x| style.patch(*x))
+    }
+
+    fn push(&mut self, text: String) {
+        let style = self.style();
+        self.spans.push(Span::styled(text, style));
+    }
+
+    /// End the line being built, if any.
+    fn flush(&mut self) {
+        if self.spans.is_empty() {
+            return;
+        }
+        let mut spans = Vec::new();
+        if self.quotes > 0 {
+            spans.push(Span::styled(
+                "│ ".repeat(self.quotes),
+                MarkdownStyle::quote(),
+            ));
+        }
+        spans.append(&mut self.spans);
+        self.lines.push(Line::from(spans));
+    }
+
+    /// End the line being built and leave an empty line after it, as between blocks.
+    fn end_block(&mut self) {
+        self.flush();
+        if self.lines.last().is_some_and(|x| !x.spans.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => {
+                for line in text.lines() {
+                    self.push(format!("  {}", line));
+                    self.flush();
+                }
+            }
+            Event::Text(text) | Event::Html(text) => self.push(text.to_string()),
+            Event::Code(text) => self
+                .spans
+                .push(Span::styled(text.to_string(), MarkdownStyle::code())),
+            Event::SoftBreak => self.push(" ".to_string()),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                self.push("─".repeat(20));
+                self.end_block();
+            }
+            Event::TaskListMarker(done) => {
+                self.push(if done { "[x] " } else { "[ ] " }.to_string())
+            }
+            Event::FootnoteReference(label) => self.push(format!("[{}]", label)),
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Heading(level, _, _) => {
+                self.flush();
+                self.styles.push(MarkdownStyle::heading());
+                self.push(format!("{} ", "#".repeat(level as usize)));
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.quotes += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                self.code_block = true;
+                self.styles.push(MarkdownStyle::code());
+                if let CodeBlockKind::Fenced(lang) = kind {
+                    if !lang.is_empty() {
+                        self.push(format!("  [{}]", lang));
+                        self.flush();
+                    }
+                }
+            }
+            Tag::List(first) => {
+                self.flush();
+                self.lists.push(first);
+            }
+            Tag::Item => {
+                self.flush();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.spans
+                    .push(Span::styled(indent + &bullet, MarkdownStyle::bullet()));
+            }
+            Tag::Emphasis => self.styles.push(Style::new().italic()),
+            Tag::Strong => self.styles.push(Style::new().bold()),
+            Tag::Strikethrough => self.styles.push(Style::new().crossed_out()),
+            Tag::Link(..) | Tag::Image(..) => self.styles.push(MarkdownStyle::link()),
+            _ => (),
+        }
+    }
+
+    fn end(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph | Tag::Heading(..) => {
+                if matches!(tag, Tag::Heading(..)) {
+                    self.styles.pop();
+                }
+                // Paragraphs of a list item stay together.
+                if self.lists.is_empty() {
+                    self.end_block();
+                } else {
+                    self.flush();
+                }
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.quotes -= 1;
+                self.end_block();
+            }
+            Tag::CodeBlock(_) => {
+                self.code_block = false;
+                self.styles.pop();
+                self.end_block();
+            }
+            Tag::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+            }
+            Tag::Item => self.flush(),
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => {
+                self.styles.pop();
+            }
+            Tag::Link(_, url, _) | Tag::Image(_, url, _) => {
+                self.styles.pop();
+                self.spans
+                    .push(Span::styled(format!(" ({})", url), MarkdownStyle::url()));
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn lines(markdown: &str) -> Vec<String> {
+        render(markdown)
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|x| x.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            lines("# Title\n\nSome *text*\nwrapped.\n\n```rust\nfn main() {}\n```"),
+            vec![
+                "# Title",
+                "",
+                "Some text wrapped.",
+                "",
+                "  [rust]",
+                "  fn main() {}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            lines("1. one\n2. two\n   - nested\n\nafter"),
+            vec!["1. one", "2. two", "  • nested", "", "after"]
+        );
+    }
+
+    #[test]
+    fn test_styles() {
+        let text = render("**bold** and [link](https://example.com)");
+        let spans = &text.lines[0].spans;
+        assert_eq!(spans[0].content, "bold");
+        assert_eq!(spans[0].style, Style::new().bold());
+        assert_eq!(spans[2].content, "link");
+        assert_eq!(spans[2].style, MarkdownStyle::link());
+        assert_eq!(spans[3].content, " (https://example.com)");
+    }
+}
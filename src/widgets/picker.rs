@@ -0,0 +1,297 @@
+use color_eyre::{eyre::eyre, Result};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::style::{FormStyle, TableStyle};
+
+/// An entry of a [`Picker`]. Entries created on the fly have no database id yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickerItem {
+    pub id: Option<i32>,
+    pub label: String,
+    pub selected: bool,
+}
+
+impl PickerItem {
+    pub fn new(id: i32, label: String) -> Self {
+        PickerItem {
+            id: Some(id),
+            label,
+            selected: false,
+        }
+    }
+}
+
+/// A searchable list of items where one or several of them can be selected.
+///
+/// Typed characters narrow the list down, `Enter` toggles the highlighted item and, when
+/// creation is allowed, a filter that matches no item exactly can be added as a new one.
+#[derive(Debug, Default)]
+pub struct Picker {
+    items: Vec<PickerItem>,
+    filter: String,
+    state: ListState,
+    multi: bool,
+    allow_create: bool,
+}
+
+impl Picker {
+    pub fn new(items: Vec<PickerItem>, multi: bool) -> Self {
+        let mut picker = Picker {
+            items,
+            multi,
+            ..Default::default()
+        };
+        picker.state.select(Some(0));
+        picker
+    }
+
+    pub fn allow_create(mut self, allow_create: bool) -> Self {
+        self.allow_create = allow_create;
+        self
+    }
+
+    pub fn items(&self) -> &[PickerItem] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Vec<&PickerItem> {
+        self.items.iter().filter(|x| x.selected).collect()
+    }
+
+    pub fn select_ids(&mut self, ids: &[i32]) {
+        for item in self.items.iter_mut() {
+            item.selected = item.id.is_some_and(|id| ids.contains(&id));
+        }
+    }
+
+    /// Select exactly the items labelled `labels`, case-insensitively, creating the missing
+    /// ones when creation is allowed.
+    pub fn select_labels(&mut self, labels: &[&str]) -> Result<()> {
+        let mut selected = Vec::new();
+        for label in labels {
+            let label = label.trim();
+            if let Some(idx) = self
+                .items
+                .iter()
+                .position(|x| x.label.to_lowercase() == label.to_lowercase())
+            {
+                selected.push(idx);
+            } else if self.allow_create {
+                self.items.push(PickerItem {
+                    id: None,
+                    label: label.to_string(),
+                    selected: false,
+                });
+                selected.push(self.items.len() - 1);
+            } else {
+                return Err(eyre!("Unknown value \"{}\"", label));
+            }
+        }
+        if !self.multi && selected.len() > 1 {
+            return Err(eyre!("Only one value can be selected"));
+        }
+        for (i, item) in self.items.iter_mut().enumerate() {
+            item.selected = selected.contains(&i);
+        }
+        Ok(())
+    }
+
+    pub fn input(&mut self, c: char) {
+        if c == '\n' {
+            self.toggle();
+        } else {
+            self.filter.push(c);
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn delete_last_char(&mut self) {
+        self.filter.pop();
+        self.state.select(Some(0));
+    }
+
+    pub fn up(&mut self) {
+        let i = self.state.selected().unwrap_or(0);
+        self.state.select(Some(i.saturating_sub(1)));
+    }
+
+    pub fn down(&mut self) {
+        let len = self.num_entries();
+        let i = self.state.selected().unwrap_or(0);
+        if i + 1 < len {
+            self.state.select(Some(i + 1));
+        }
+    }
+
+    /// Toggle the highlighted entry, creating it first if it is the "create" entry.
+    pub fn toggle(&mut self) {
+        let Some(cursor) = self.state.selected() else {
+            return;
+        };
+        let filtered = self.filtered();
+        let idx = if let Some(idx) = filtered.get(cursor) {
+            *idx
+        } else if cursor == filtered.len() && self.can_create() {
+            self.items.push(PickerItem {
+                id: None,
+                label: self.filter.trim().to_string(),
+                selected: false,
+            });
+            self.filter.clear();
+            self.items.len() - 1
+        } else {
+            return;
+        };
+
+        let selected = !self.items[idx].selected;
+        if selected && !self.multi {
+            for item in self.items.iter_mut() {
+                item.selected = false;
+            }
+        }
+        self.items[idx].selected = selected;
+    }
+
+    /// Indices of the items matching the filter, case-insensitively.
+    fn filtered(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.label.to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn can_create(&self) -> bool {
+        let filter = self.filter.trim().to_lowercase();
+        self.allow_create
+            && !filter.is_empty()
+            && !self.items.iter().any(|x| x.label.to_lowercase() == filter)
+    }
+
+    fn num_entries(&self) -> usize {
+        self.filtered().len() + usize::from(self.can_create())
+    }
+
+    pub fn draw(&mut self, f: &mut Frame<'_>, area: Rect, block: Block<'_>, focused: bool) {
+        if !focused {
+            let labels: Vec<String> = self.selected().iter().map(|x| x.label.clone()).collect();
+            f.render_widget(Paragraph::new(labels.join(", ")).block(block), area);
+            return;
+        }
+
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(inner);
+
+        f.render_widget(
+            Paragraph::new(format!("Filter: {}", self.filter)).style(FormStyle::normal()),
+            layout[0],
+        );
+
+        let mut entries: Vec<ListItem> = self
+            .filtered()
+            .into_iter()
+            .map(|i| {
+                let item = &self.items[i];
+                let mark = if item.selected { "[x] " } else { "[ ] " };
+                ListItem::new(Line::from(vec![
+                    Span::raw(mark),
+                    Span::raw(item.label.clone()),
+                ]))
+            })
+            .collect();
+        if self.can_create() {
+            entries.push(ListItem::new(format!(
+                "[+] Create \"{}\"",
+                self.filter.trim()
+            )));
+        }
+        let list = List::new(entries)
+            .style(FormStyle::normal())
+            .highlight_style(TableStyle::highlighted());
+        f.render_stateful_widget(list, layout[1], &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn picker(multi: bool) -> Picker {
+        Picker::new(
+            vec![
+                PickerItem::new(1, "Rust".to_string()),
+                PickerItem::new(2, "Web".to_string()),
+                PickerItem::new(3, "Trust".to_string()),
+            ],
+            multi,
+        )
+        .allow_create(true)
+    }
+
+    #[test]
+    fn test_filter_is_case_insensitive() {
+        let mut p = picker(true);
+        "RUST".chars().for_each(|c| p.input(c));
+        assert_eq!(p.filtered(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_toggle_multi() {
+        let mut p = picker(true);
+        p.toggle();
+        p.down();
+        p.toggle();
+        let ids: Vec<Option<i32>> = p.selected().iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_toggle_single() {
+        let mut p = picker(false);
+        p.toggle();
+        p.down();
+        p.toggle();
+        let ids: Vec<Option<i32>> = p.selected().iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![Some(2)]);
+    }
+
+    #[test]
+    fn test_create_on_the_fly() {
+        let mut p = picker(true);
+        "blog".chars().for_each(|c| p.input(c));
+        p.input('\n');
+        let selected = p.selected();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].id, None);
+        assert_eq!(selected[0].label, "blog");
+    }
+
+    #[test]
+    fn test_select_labels() {
+        let mut p = picker(true);
+        p.select_labels(&["web", "blog"]).unwrap();
+        let labels: Vec<&str> = p.selected().iter().map(|x| x.label.as_str()).collect();
+        assert_eq!(labels, vec!["Web", "blog"]);
+        assert!(picker(false).select_labels(&["rust", "web"]).is_err());
+    }
+
+    #[test]
+    fn test_no_create_for_existing_label() {
+        let mut p = picker(true);
+        "web".chars().for_each(|c| p.input(c));
+        assert!(!p.can_create());
+    }
+}